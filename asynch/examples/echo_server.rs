@@ -0,0 +1,39 @@
+use std::io;
+
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+use asynch::{AsyncTcpListener, AsyncTcpStream, Runtime};
+
+/// 与echo服务器相同的处理逻辑，但每个连接是一个异步任务而不是一个线程
+async fn handle_client(mut stream: AsyncTcpStream) -> io::Result<()> {
+    println!("Incoming connection from: {}", stream.peer_addr()?);
+    let mut buf = [0; 1024];
+    loop {
+        let bytes_read = stream.read(&mut buf).await?;
+        if bytes_read == 0 { return Ok(()); }
+        stream.write_all(&buf[..bytes_read]).await?;
+    }
+}
+
+fn main() {
+    let runtime = Runtime::new();
+    let spawner = runtime.spawner();
+    runtime.block_on(async move {
+        let listener = AsyncTcpListener::bind("127.0.0.1:8080")
+            .expect("Could not bind");
+        loop {
+            match listener.accept().await {
+                Err(e) => eprintln!("failed: {}", e),
+                Ok((stream, _)) => {
+                    let spawned = spawner.spawn(async move {
+                        handle_client(stream).await
+                            .unwrap_or_else(|error| eprintln!("{:?}", error));
+                    });
+                    if let Err(error) = spawned {
+                        eprintln!("failed: {}", error);
+                    }
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,42 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread::{self, Thread};
+
+use futures::task::{waker_ref, ArcWake};
+
+/// 唤醒调用block_on的线程
+struct ThreadNotify {
+    thread: Thread,  // 调用block_on的线程
+    notified: AtomicBool,  // 休眠前是否已被唤醒，避免丢失唤醒
+}
+
+impl ArcWake for ThreadNotify {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.notified.store(true, Ordering::SeqCst);
+        arc_self.thread.unpark();
+    }
+}
+
+/// 在当前线程上运行future直到完成并返回其结果，供同步代码（如main）使用
+///
+/// future等待期间当前线程休眠；不要在运行时的任务中调用，否则会阻塞工作线程
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let notify = Arc::new(ThreadNotify {
+        thread: thread::current(),
+        notified: AtomicBool::new(false),
+    });
+    let waker = waker_ref(&notify);
+    let context = &mut Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(context) {
+            return output;
+        }
+        while !notify.notified.swap(false, Ordering::SeqCst) {
+            thread::park();
+        }
+    }
+}
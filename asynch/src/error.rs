@@ -0,0 +1,84 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+
+/// 提交任务失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnError {
+    QueueFull,  // 就绪队列已满
+    Shutdown,  // 运行时已关闭
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SpawnError::QueueFull => write!(f, "too many tasks queued"),
+            SpawnError::Shutdown => write!(f, "runtime has been shut down"),
+        }
+    }
+}
+
+impl Error for SpawnError {}
+
+/// 任务未能正常返回结果的原因
+pub enum JoinError {
+    Panicked(Box<dyn Any + Send>),  // 任务发生恐慌，携带恐慌的payload
+    Cancelled,  // 任务未完成就被丢弃，如运行时已关闭
+}
+
+impl JoinError {
+    /// 任务是否因恐慌而结束
+    pub fn is_panic(&self) -> bool {
+        matches!(*self, JoinError::Panicked(_))
+    }
+    /// 任务是否被取消
+    pub fn is_cancelled(&self) -> bool {
+        matches!(*self, JoinError::Cancelled)
+    }
+    /// 获取恐慌的payload，任务并非因恐慌结束时会引发恐慌
+    pub fn into_panic(self) -> Box<dyn Any + Send> {
+        match self {
+            JoinError::Panicked(payload) => payload,
+            JoinError::Cancelled => panic!("task was cancelled, not panicked"),
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JoinError::Panicked(_) => f.write_str("Panicked(..)"),
+            JoinError::Cancelled => f.write_str("Cancelled"),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JoinError::Panicked(ref payload) => {
+                let message = payload.downcast_ref::<&str>().copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+                match message {
+                    Some(message) => write!(f, "task panicked: {}", message),
+                    None => write!(f, "task panicked"),
+                }
+            }
+            JoinError::Cancelled => write!(f, "task was cancelled before it completed"),
+        }
+    }
+}
+
+impl Error for JoinError {}
+
+/// `timeout`等待的future未能在时限内完成
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
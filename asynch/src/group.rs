@@ -0,0 +1,61 @@
+use std::future::Future;
+use std::sync::Mutex;
+
+use super::{AbortHandle, JoinHandle, Runtime, SpawnError, Spawner};
+
+/// 任务组，通过它提交的子任务在任务组被丢弃时全部取消
+///
+/// 将任务组保存在父任务中，父任务结束或被取消时子任务随之取消
+pub struct TaskGroup {
+    spawner: Spawner,
+    children: Mutex<Vec<AbortHandle>>,  // 子任务的取消句柄
+}
+
+impl TaskGroup {
+    pub fn new(spawner: Spawner) -> TaskGroup {
+        TaskGroup {
+            spawner,
+            children: Mutex::new(Vec::new()),
+        }
+    }
+    /// 在任务组中提交子任务
+    pub fn spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>, SpawnError>
+        where F: Future + Send + 'static,
+              F::Output: Send + 'static
+    {
+        let handle = self.spawner.spawn(future)?;
+        let mut children = self.children.lock()
+            .expect("unable to lock task group");
+        children.retain(|child| !child.is_released());  // 顺便清理已经释放的子任务
+        children.push(handle.abort_handle());
+        Ok(handle)
+    }
+    /// 取消任务组中所有尚未结束的子任务
+    pub fn abort_all(&self) {
+        let children = std::mem::take(&mut *self.children.lock()
+            .expect("unable to lock task group"));
+        for child in children {
+            child.abort();
+        }
+    }
+}
+
+impl Drop for TaskGroup {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
+
+impl Spawner {
+    /// 创建在本运行时中提交子任务的任务组
+    pub fn task_group(&self) -> TaskGroup {
+        TaskGroup::new(self.clone())
+    }
+}
+
+impl Runtime {
+    /// 创建在本运行时中提交子任务的任务组
+    pub fn task_group(&self) -> TaskGroup {
+        TaskGroup::new(self.spawner())
+    }
+}
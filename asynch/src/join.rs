@@ -0,0 +1,185 @@
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+
+use futures::future::{CatchUnwind, FutureExt};
+
+use super::runtime::Task;
+use super::JoinError;
+
+/// 在任务与JoinHandle之间共享的状态
+struct JoinState<T> {
+    result: Option<Result<T, JoinError>>,  // 任务结果，取走后为None
+    finished: bool,  // 任务是否已经结束
+    waker: Option<Waker>,  // 等待结果的任务的waker
+}
+
+impl<T> JoinState<T> {
+    fn complete(state: &Mutex<JoinState<T>>, result: Result<T, JoinError>) {
+        let mut state = state.lock()
+            .expect("unable to lock join state");
+        state.result = Some(result);
+        state.finished = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// 任务结束、JoinHandle得到结果之前调用的回调
+pub(crate) type OnComplete = Box<dyn FnOnce() + Send>;
+
+/// 随任务一起保存，任务未完成就被丢弃时将结果设置为`Cancelled`
+struct CompletionGuard<T> {
+    state: Option<Arc<Mutex<JoinState<T>>>>,
+    on_complete: Option<OnComplete>,  // 运行时据此先注销任务，保证JoinHandle返回后任务不再出现在快照中
+}
+
+impl<T> CompletionGuard<T> {
+    fn complete(&mut self, result: Result<T, JoinError>) {
+        if let Some(state) = self.state.take() {
+            if let Some(on_complete) = self.on_complete.take() {
+                on_complete();
+            }
+            JoinState::complete(&state, result);
+        }
+    }
+}
+
+impl<T> Drop for CompletionGuard<T> {
+    fn drop(&mut self) {
+        self.complete(Err(JoinError::Cancelled));
+    }
+}
+
+/// 运行时实际调度的future，完成时将结果交给JoinHandle
+///
+/// 字段按声明顺序释放：先丢弃用户的future，再设置`Cancelled`，
+/// 因此JoinHandle得到结果时future的析构已经执行完毕
+pub(crate) struct TaskFuture<F: Future> {
+    future: Option<Pin<Box<CatchUnwind<AssertUnwindSafe<F>>>>>,  // 完成后为None
+    guard: CompletionGuard<F::Output>,
+}
+
+impl<F: Future> Future for TaskFuture<F> {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        let future = match this.future.as_mut() {
+            Some(future) => future,
+            None => return Poll::Ready(()),
+        };
+        let result = match future.as_mut().poll(cx) {
+            Poll::Ready(result) => result.map_err(JoinError::Panicked),
+            Poll::Pending => return Poll::Pending,
+        };
+        this.future = None;
+        this.guard.complete(result);
+        Poll::Ready(())
+    }
+}
+
+/// 取消任务的句柄，可以独立于JoinHandle保存
+#[derive(Clone)]
+pub struct AbortHandle {
+    task: Weak<Task>,  // 任务结束后无法升级，取消不再有效果
+}
+
+impl AbortHandle {
+    /// 取消任务，任务的future会在工作线程上被丢弃，等待中的JoinHandle得到`Cancelled`
+    ///
+    /// 已经结束的任务不受影响；正在执行的任务在本次poll返回后被丢弃
+    pub fn abort(&self) {
+        if let Some(task) = self.task.upgrade() {
+            task.abort();
+        }
+    }
+
+    /// 任务是否已被运行时释放
+    pub(crate) fn is_released(&self) -> bool {
+        self.task.strong_count() == 0
+    }
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AbortHandle").finish()
+    }
+}
+
+/// `spawn`返回的任务句柄，本身是一个Future，完成时得到任务的结果
+///
+/// 丢弃句柄不会取消任务
+pub struct JoinHandle<T> {
+    state: Arc<Mutex<JoinState<T>>>,
+    task: Weak<Task>,  // 用于取消任务
+}
+
+impl<T> JoinHandle<T> {
+    /// 任务是否已经结束
+    pub fn is_finished(&self) -> bool {
+        self.state.lock()
+            .expect("unable to lock join state")
+            .finished
+    }
+    /// 取消任务，见`AbortHandle::abort`
+    pub fn abort(&self) {
+        self.abort_handle().abort();
+    }
+    /// 获取可以在其他地方取消任务的句柄
+    pub fn abort_handle(&self) -> AbortHandle {
+        AbortHandle { task: self.task.clone() }
+    }
+
+    pub(crate) fn set_task(&mut self, task: Weak<Task>) {
+        self.task = task;
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock()
+            .expect("unable to lock join state");
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                assert!(!state.finished, "JoinHandle polled after completion");
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// 将future包装为运行时可以调度的任务，任务的结果通过返回的JoinHandle获取
+///
+/// `on_complete`在任务完成或被丢弃后、JoinHandle得到结果之前调用
+pub(crate) fn join_pair<F>(future: F, on_complete: OnComplete) -> (TaskFuture<F>, JoinHandle<F::Output>)
+    where F: Future
+{
+    let state = Arc::new(Mutex::new(JoinState {
+        result: None,
+        finished: false,
+        waker: None,
+    }));
+    let task = TaskFuture {
+        future: Some(Box::pin(AssertUnwindSafe(future).catch_unwind())),
+        guard: CompletionGuard {
+            state: Some(state.clone()),
+            on_complete: Some(on_complete),
+        },
+    };
+    (task, JoinHandle { state, task: Weak::new() })
+}
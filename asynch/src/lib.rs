@@ -0,0 +1,21 @@
+mod block_on;
+mod error;
+mod group;
+mod join;
+mod net;
+mod reactor;
+mod runtime;
+mod select;
+mod stats;
+pub mod sync;
+mod timer;
+
+pub use block_on::block_on;
+pub use error::{Elapsed, JoinError, SpawnError};
+pub use group::TaskGroup;
+pub use join::{AbortHandle, JoinHandle};
+pub use net::{AsyncTcpListener, AsyncTcpStream};
+pub use runtime::{Builder, Runtime, Spawner};
+pub use select::{select, select_all, Either, Select, SelectAll};
+pub use stats::{TaskInfo, TaskState};
+pub use timer::{interval, pending_timers, sleep, sleep_until, timeout, Interval, Timeout, TimerFuture};
@@ -1,128 +1,26 @@
-use {
-    futures::{
-        future::{BoxFuture, FutureExt},
-        task::{waker_ref, ArcWake},
-    },
-    std::{
-        future::Future,
-        pin::Pin,
-        sync::mpsc::{sync_channel, Receiver, SyncSender},
-        sync::{Arc, Mutex},
-        task::{Context, Poll, Waker},
-        thread,
-        time::Duration,
-    },
-};
+use std::time::Duration;
 
-pub struct TimerFuture {
-    shared_state: Arc<Mutex<SharedState>>,  // 通过Arc<Mutex<..>>在线程和Future之间共享数据
-}
-
-/// 在future和等待的线程之间共享状态
-struct SharedState {
-    completed: bool, // 睡眠是否完成
-    waker: Option<Waker>, // TimerFuture
-}
-
-impl Future for TimerFuture {
-    type Output = ();
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut shared_state = self.shared_state.lock().unwrap();
-        if shared_state.completed {
-            Poll::Ready(())
-        } else {
-            shared_state.waker = Some(cx.waker().clone());
-            Poll::Pending
-        }
-    }
-}
-
-impl TimerFuture {
-    pub fn new(duration: Duration) -> Self {
-        let shared_state = Arc::new(Mutex::new(SharedState {
-            completed: false,
-            waker: None,
-        }));
-
-        let thread_shared_state = shared_state.clone();
-        thread::spawn(move || {
-            thread::sleep(duration);
-            let mut shared_state = thread_shared_state.lock().unwrap();
-            shared_state.completed = true;
-            if let Some(waker) = shared_state.waker.take() {
-                waker.wake()
-            }
-        });
-        TimerFuture { shared_state }
-    }
-}
-
-struct Executor {
-    ready_queue: Receiver<Arc<Task>>,
-}
-
-#[derive(Clone)]
-struct Spawner {
-    task_sender: SyncSender<Arc<Task>>,
-}
-
-struct Task {
-    future: Mutex<Option<BoxFuture<'static, ()>>>,
-    task_sender: SyncSender<Arc<Task>>,
-}
-
-fn new_executor_and_spawner() -> (Executor, Spawner) {
-    const MAX_QUEUED_TASKS: usize = 10_000;
-    let (task_sender, ready_queue) = sync_channel(MAX_QUEUED_TASKS);
-    (Executor { ready_queue }, Spawner { task_sender })
-}
-
-impl Spawner {
-    fn spawn(&self, future: impl Future<Output=()> + 'static + Send) {
-        let future = future.boxed();
-        let task = Arc::new(Task {
-            future: Mutex::new(Some(future)),
-            task_sender: self.task_sender.clone(),
-        });
-        self.task_sender.send(task).expect("too many tasks queued");
-    }
-}
-
-impl ArcWake for Task {
-    fn wake_by_ref(arc_self: &Arc<Self>) {
-        let cloned = arc_self.clone();
-        arc_self
-            .task_sender
-            .send(cloned)
-            .expect("too many tasks queued");
-    }
-}
-
-impl Executor {
-    fn run(&self) {
-        while let Ok(task) = self.ready_queue.recv() {
-            let mut future_slot = task.future.lock().unwrap();
-            if let Some(mut future) = future_slot.take() {
-                let waker = waker_ref(&task);
-                let context = &mut Context::from_waker(&*waker);
-                if let Poll::Pending = future.as_mut().poll(context) {
-                    *future_slot = Some(future);
-                }
-            }
-        }
-    }
-}
+use asynch::{Runtime, TimerFuture};
+use thread_pool::ThreadPool;
 
 fn main() {
-    let (executor, spawner) = new_executor_and_spawner();
+    let runtime = Runtime::new();
 
-    spawner.spawn(async {
+    let timer = runtime.spawn(async {
         println!("howdy!");
         TimerFuture::new(Duration::new(2, 0)).await;
         println!("done!");
+    }).expect("unable to spawn task");
+
+    // 在线程池中执行阻塞计算，并在异步任务中等待其结果
+    let pool = ThreadPool::new(2);
+    let handle = pool.spawn(|| (1..=100).sum::<u32>());
+    let sum = runtime.spawn(async move {
+        handle.await.expect("sum task panicked")
+    }).expect("unable to spawn task");
+
+    runtime.block_on(async {
+        println!("sum: {}", sum.await.expect("sum task failed"));
+        timer.await.expect("timer task failed");
     });
-
-    drop(spawner);
-
-    executor.run();
 }
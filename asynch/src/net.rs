@@ -0,0 +1,167 @@
+use std::io::{self, Read, Write};
+use std::mem;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::future::poll_fn;
+use futures::io::{AsyncRead, AsyncWrite};
+
+use super::reactor::{syscall, Registration};
+
+/// 非阻塞的TCP监听器，等待连接时不占用工作线程
+pub struct AsyncTcpListener {
+    registration: Registration,  // 先于inner释放，保证注销时描述符仍然有效
+    inner: TcpListener,
+}
+
+impl AsyncTcpListener {
+    /// 绑定地址并开始监听
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<AsyncTcpListener> {
+        AsyncTcpListener::from_std(TcpListener::bind(addr)?)
+    }
+    /// 将标准库的监听器转换为非阻塞模式并注册到reactor
+    pub fn from_std(inner: TcpListener) -> io::Result<AsyncTcpListener> {
+        inner.set_nonblocking(true)?;
+        let registration = Registration::new(inner.as_raw_fd())?;
+        Ok(AsyncTcpListener { registration, inner })
+    }
+    /// 等待并接受一个连接
+    pub async fn accept(&self) -> io::Result<(AsyncTcpStream, SocketAddr)> {
+        let (stream, addr) = poll_fn(|cx| {
+            self.registration.poll_read(cx, || self.inner.accept())
+        }).await?;
+        Ok((AsyncTcpStream::from_std(stream)?, addr))
+    }
+    /// 监听的本地地址
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+}
+
+/// 非阻塞的TCP连接，实现了`futures::io`的`AsyncRead`和`AsyncWrite`
+pub struct AsyncTcpStream {
+    registration: Registration,  // 先于inner释放，保证注销时描述符仍然有效
+    inner: TcpStream,
+}
+
+impl AsyncTcpStream {
+    /// 连接到指定地址，解析出多个地址时依次尝试
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<AsyncTcpStream> {
+        let mut last_error = None;
+        for addr in addr.to_socket_addrs()? {
+            match AsyncTcpStream::connect_addr(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "could not resolve to any address")
+        }))
+    }
+
+    async fn connect_addr(addr: SocketAddr) -> io::Result<AsyncTcpStream> {
+        let stream = AsyncTcpStream::from_std(nonblocking_connect(addr)?)?;
+        // 非阻塞连接在描述符可写时完成，通过SO_ERROR和peer_addr判断结果
+        poll_fn(|cx| {
+            stream.registration.poll_write(cx, || {
+                if let Some(error) = stream.inner.take_error()? {
+                    return Err(error);
+                }
+                match stream.inner.peer_addr() {
+                    Ok(_) => Ok(()),
+                    Err(ref error) if error.kind() == io::ErrorKind::NotConnected => {
+                        Err(io::ErrorKind::WouldBlock.into())
+                    }
+                    Err(error) => Err(error),
+                }
+            })
+        }).await?;
+        Ok(stream)
+    }
+
+    /// 将标准库的连接转换为非阻塞模式并注册到reactor
+    pub fn from_std(inner: TcpStream) -> io::Result<AsyncTcpStream> {
+        inner.set_nonblocking(true)?;
+        let registration = Registration::new(inner.as_raw_fd())?;
+        Ok(AsyncTcpStream { registration, inner })
+    }
+    /// 对端地址
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+    /// 本地地址
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+    /// 关闭连接的读、写或两个方向
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.inner.shutdown(how)
+    }
+}
+
+impl AsyncRead for AsyncTcpStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.registration.poll_read(cx, || (&this.inner).read(buf))
+    }
+}
+
+impl AsyncWrite for AsyncTcpStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.registration.poll_write(cx, || (&this.inner).write(buf))
+    }
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))  // TCP连接没有用户态缓冲
+    }
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.inner.shutdown(Shutdown::Write))
+    }
+}
+
+/// 创建非阻塞套接字并发起连接，连接通常尚未完成
+fn nonblocking_connect(addr: SocketAddr) -> io::Result<TcpStream> {
+    let family = match addr {
+        SocketAddr::V4(_) => libc::AF_INET,
+        SocketAddr::V6(_) => libc::AF_INET6,
+    };
+    let fd = syscall(unsafe {
+        libc::socket(family, libc::SOCK_STREAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC, 0)
+    })?;
+    let stream = unsafe { TcpStream::from_raw_fd(fd) };  // 由TcpStream负责关闭描述符
+    let (storage, len) = raw_socket_addr(&addr);
+    let result = syscall(unsafe {
+        libc::connect(fd, &storage as *const libc::sockaddr_storage as *const libc::sockaddr, len)
+    });
+    match result {
+        Ok(_) => Ok(stream),
+        Err(ref error) if error.raw_os_error() == Some(libc::EINPROGRESS) => Ok(stream),
+        Err(error) => Err(error),
+    }
+}
+
+/// 将SocketAddr转换为系统调用使用的地址结构
+fn raw_socket_addr(addr: &SocketAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let len = match *addr {
+        SocketAddr::V4(ref addr) => {
+            let sin = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in) };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = addr.port().to_be();
+            sin.sin_addr.s_addr = u32::from_ne_bytes(addr.ip().octets());
+            mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(ref addr) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = addr.port().to_be();
+            sin6.sin6_flowinfo = addr.flowinfo();
+            sin6.sin6_addr.s6_addr = addr.ip().octets();
+            sin6.sin6_scope_id = addr.scope_id();
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+    (storage, len as libc::socklen_t)
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// 每次epoll_wait最多取出的事件数
+const MAX_EVENTS: usize = 1024;
+
+/// 某一方向（读或写）的就绪状态
+struct ReadyState {
+    ready: bool,  // 是否可能就绪，操作返回WouldBlock后清除
+    tick: u64,  // 每收到一次事件加一，用于判断清除就绪状态时是否有新事件到来
+    waker: Option<Waker>,  // 等待就绪的任务
+}
+
+/// 在线程之间共享的单个方向的就绪状态
+pub(crate) struct Direction {
+    state: Mutex<ReadyState>,
+}
+
+impl Direction {
+    fn new() -> Direction {
+        Direction {
+            state: Mutex::new(ReadyState {
+                ready: true,  // 注册前可能已有数据，先尝试一次操作
+                tick: 0,
+                waker: None,
+            }),
+        }
+    }
+
+    /// 就绪时返回当前的tick，否则登记waker
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<u64> {
+        let mut state = self.state.lock()
+            .expect("unable to lock io readiness");
+        if state.ready {
+            Poll::Ready(state.tick)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    /// 操作返回WouldBlock后清除就绪状态，期间收到过新事件则保留
+    fn clear_ready(&self, tick: u64) {
+        let mut state = self.state.lock()
+            .expect("unable to lock io readiness");
+        if state.tick == tick {
+            state.ready = false;
+        }
+    }
+
+    fn wake(&self) {
+        let mut state = self.state.lock()
+            .expect("unable to lock io readiness");
+        state.ready = true;
+        state.tick += 1;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// 已注册到reactor的文件描述符的读写就绪状态
+struct ScheduledIo {
+    read: Direction,
+    write: Direction,
+}
+
+/// 基于epoll的I/O reactor，由一个后台线程等待事件并唤醒对应的任务
+struct Reactor {
+    epoll_fd: RawFd,
+    sources: Mutex<HashMap<u64, Arc<ScheduledIo>>>,  // 按token索引的已注册描述符
+    next_token: AtomicU64,  // 下一个token
+}
+
+static REACTOR: OnceLock<Reactor> = OnceLock::new();
+
+/// 获取全局reactor，首次使用时创建epoll实例并启动事件线程
+fn reactor() -> io::Result<&'static Reactor> {
+    if let Some(reactor) = REACTOR.get() {
+        return Ok(reactor);
+    }
+    let epoll_fd = syscall(unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) })?;
+    let mut created = false;
+    let reactor = REACTOR.get_or_init(|| {
+        created = true;
+        thread::Builder::new()
+            .name("asynch-reactor".to_owned())
+            .spawn(|| REACTOR.wait().run())
+            .expect("unable to spawn reactor thread");
+        Reactor {
+            epoll_fd,
+            sources: Mutex::new(HashMap::new()),
+            next_token: AtomicU64::new(0),
+        }
+    });
+    if !created {  // 其他线程抢先完成了初始化
+        unsafe { libc::close(epoll_fd) };
+    }
+    Ok(reactor)
+}
+
+impl Reactor {
+    /// 事件线程循环：等待事件并唤醒读写方向上等待的任务
+    fn run(&self) {
+        let mut events: Vec<libc::epoll_event> = Vec::with_capacity(MAX_EVENTS);
+        loop {
+            let count = unsafe {
+                libc::epoll_wait(self.epoll_fd, events.as_mut_ptr(), MAX_EVENTS as libc::c_int, -1)
+            };
+            let count = match syscall(count) {
+                Ok(count) => count as usize,
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => panic!("epoll_wait failed: {}", error),
+            };
+            unsafe { events.set_len(count) };
+            for event in events.iter() {
+                let flags = event.events as libc::c_int;
+                let token = event.u64;
+                let io = self.sources.lock()
+                    .expect("unable to lock io sources")
+                    .get(&token)
+                    .cloned();
+                let io = match io {
+                    Some(io) => io,
+                    None => continue,  // 已注销的描述符
+                };
+                if flags & (libc::EPOLLIN | libc::EPOLLRDHUP | libc::EPOLLHUP | libc::EPOLLERR) != 0 {
+                    io.read.wake();
+                }
+                if flags & (libc::EPOLLOUT | libc::EPOLLHUP | libc::EPOLLERR) != 0 {
+                    io.write.wake();
+                }
+            }
+        }
+    }
+}
+
+/// 文件描述符在reactor中的注册，释放时注销
+pub(crate) struct Registration {
+    fd: RawFd,
+    token: u64,
+    io: Arc<ScheduledIo>,
+}
+
+impl Registration {
+    /// 以边沿触发方式注册非阻塞的文件描述符
+    pub(crate) fn new(fd: RawFd) -> io::Result<Registration> {
+        let reactor = reactor()?;
+        let token = reactor.next_token.fetch_add(1, Ordering::Relaxed);
+        let io = Arc::new(ScheduledIo {
+            read: Direction::new(),
+            write: Direction::new(),
+        });
+        reactor.sources.lock()
+            .expect("unable to lock io sources")
+            .insert(token, io.clone());
+        let mut event = libc::epoll_event {
+            events: (libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLRDHUP | libc::EPOLLET) as u32,
+            u64: token,
+        };
+        let result = syscall(unsafe {
+            libc::epoll_ctl(reactor.epoll_fd, libc::EPOLL_CTL_ADD, fd, &mut event)
+        });
+        if let Err(error) = result {
+            reactor.sources.lock()
+                .expect("unable to lock io sources")
+                .remove(&token);
+            return Err(error);
+        }
+        Ok(Registration { fd, token, io })
+    }
+
+    /// 执行非阻塞操作，返回WouldBlock时等待描述符可读后重试
+    pub(crate) fn poll_read<R, F>(&self, cx: &mut Context<'_>, op: F) -> Poll<io::Result<R>>
+        where F: FnMut() -> io::Result<R>
+    {
+        poll_io(&self.io.read, cx, op)
+    }
+
+    /// 执行非阻塞操作，返回WouldBlock时等待描述符可写后重试
+    pub(crate) fn poll_write<R, F>(&self, cx: &mut Context<'_>, op: F) -> Poll<io::Result<R>>
+        where F: FnMut() -> io::Result<R>
+    {
+        poll_io(&self.io.write, cx, op)
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Some(reactor) = REACTOR.get() {
+            unsafe {
+                libc::epoll_ctl(reactor.epoll_fd, libc::EPOLL_CTL_DEL, self.fd, std::ptr::null_mut());
+            }
+            reactor.sources.lock()
+                .expect("unable to lock io sources")
+                .remove(&self.token);
+        }
+    }
+}
+
+fn poll_io<R, F>(direction: &Direction, cx: &mut Context<'_>, mut op: F) -> Poll<io::Result<R>>
+    where F: FnMut() -> io::Result<R>
+{
+    loop {
+        let tick = match direction.poll_ready(cx) {
+            Poll::Ready(tick) => tick,
+            Poll::Pending => return Poll::Pending,
+        };
+        match op() {
+            Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => direction.clear_ready(tick),
+            result => return Poll::Ready(result),
+        }
+    }
+}
+
+/// 将返回-1表示失败的系统调用结果转换为io::Result
+pub(crate) fn syscall(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::task::Context;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::future::{BoxFuture, FutureExt};
+use futures::task::{waker_ref, ArcWake};
+
+use super::join::join_pair;
+use super::{block_on, JoinHandle, SpawnError, TaskInfo, TaskState};
+
+/// 默认的就绪队列容量
+const DEFAULT_QUEUE_CAPACITY: usize = 10_000;
+
+/// 运行时和所有任务共享的状态
+struct Shared {
+    task_sender: Sender<Option<Arc<Task>>>,  // 向就绪队列发送被唤醒的任务，None通知工作线程退出
+    ready_queue: Mutex<Receiver<Option<Arc<Task>>>>,  // 所有工作线程共享的就绪队列
+    queue_capacity: usize,  // 就绪队列容量，只限制新提交的任务，被唤醒的任务总能入队
+    queued_count: AtomicUsize,  // 就绪队列中的任务数
+    closed: AtomicBool,  // 运行时是否已关闭
+    tasks: Mutex<HashMap<u64, Weak<Task>>>,  // 尚未完成的任务，运行时关闭时据此取消任务
+    next_task_id: AtomicU64,  // 下一个任务的编号
+    idle_condvar: Condvar,  // 任务全部完成时通知join
+    slow_poll_threshold: Option<Duration>,  // 单次推进超过该时间时输出警告
+}
+
+impl Shared {
+    /// 占用就绪队列中的一个位置，队列已满时失败
+    fn try_reserve_slot(&self) -> bool {
+        let mut queued = self.queued_count.load(Ordering::SeqCst);
+        loop {
+            if queued >= self.queue_capacity {
+                return false;
+            }
+            match self.queued_count.compare_exchange_weak(
+                queued, queued + 1, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return true,
+                Err(current) => queued = current,
+            }
+        }
+    }
+
+    /// 任务完成或被丢弃时从登记表中移除，可能被调用多次
+    fn task_finished(&self, id: u64) {
+        let mut tasks = self.tasks.lock()
+            .expect("unable to lock tasks");
+        if tasks.remove(&id).is_some() && tasks.is_empty() {
+            self.idle_condvar.notify_all();
+        }
+    }
+}
+
+/// `Task::state`中保存的任务状态，只有从空闲变为已调度时才将任务放入就绪队列
+const STATE_SCHEDULED: u8 = 0;
+const STATE_RUNNING: u8 = 1;
+const STATE_IDLE: u8 = 2;
+const STATE_NOTIFIED: u8 = 3;  // 执行期间被唤醒，由执行它的工作线程在推进结束后重新入队
+
+/// 被调度的异步任务，唤醒时将自身重新放入就绪队列
+pub(crate) struct Task {
+    id: u64,  // 任务编号
+    name: Option<String>,  // 任务名称，用于诊断
+    future: Mutex<Option<BoxFuture<'static, ()>>>,  // 任务完成后为None
+    aborted: AtomicBool,  // 是否已被取消，下次调度时丢弃future
+    state: AtomicU8,  // 任务状态，取值为STATE_*
+    poll_count: AtomicU64,  // 被推进的次数
+    poll_nanos: AtomicU64,  // 推进累计花费的纳秒数
+    slow_polls: AtomicU64,  // 超过慢推进阈值的次数
+    spawned_at: Instant,  // 提交时间
+    shared: Arc<Shared>,
+}
+
+impl Task {
+    /// 推进一次任务，任务完成、恐慌或被取消时释放其future
+    fn poll(self: &Arc<Self>) {
+        let mut future_slot = self.future.lock().unwrap();
+        let mut future = match future_slot.take() {
+            Some(future) => future,
+            None => return,
+        };
+        if !self.aborted.load(Ordering::SeqCst) {
+            let waker = waker_ref(self);
+            let context = &mut Context::from_waker(&waker);
+            self.state.store(STATE_RUNNING, Ordering::SeqCst);
+            let start = Instant::now();
+            // 恐慌信息由默认的panic hook输出，任务视为结束，工作线程继续运行
+            let result = panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(context)));
+            self.record_poll(start.elapsed());
+            // 执行期间被取消的任务也在此丢弃
+            if let Ok(poll) = result {
+                if poll.is_pending() && !self.aborted.load(Ordering::SeqCst) {
+                    *future_slot = Some(future);
+                    drop(future_slot);
+                    // 执行期间被唤醒的任务由本线程重新入队，避免其他工作线程等待future的锁
+                    if self.state.compare_exchange(
+                        STATE_RUNNING, STATE_IDLE, Ordering::SeqCst, Ordering::SeqCst).is_err()
+                    {
+                        self.state.store(STATE_SCHEDULED, Ordering::SeqCst);
+                        self.schedule();
+                    }
+                    return;
+                }
+            }
+            self.state.store(STATE_IDLE, Ordering::SeqCst);
+        }
+        drop(future_slot);
+        drop(future);  // 在锁外丢弃，future的析构可能唤醒本任务
+        self.shared.task_finished(self.id);
+    }
+
+    /// 将任务放入就绪队列，运行时关闭后不再调度
+    fn schedule(self: &Arc<Self>) {
+        let shared = &self.shared;
+        if shared.closed.load(Ordering::SeqCst) {
+            return;
+        }
+        shared.queued_count.fetch_add(1, Ordering::SeqCst);
+        let _ = shared.task_sender.send(Some(self.clone()));
+    }
+
+    /// 记录一次推进的耗时，超过阈值时输出警告
+    fn record_poll(&self, elapsed: Duration) {
+        self.poll_count.fetch_add(1, Ordering::Relaxed);
+        self.poll_nanos.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        if let Some(threshold) = self.shared.slow_poll_threshold {
+            if elapsed > threshold {
+                self.slow_polls.fetch_add(1, Ordering::Relaxed);
+                eprintln!("warning: {} took {:?} to poll, exceeding the slow poll threshold of {:?}",
+                          self.label(), elapsed, threshold);
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        match self.name {
+            Some(ref name) => format!("task #{} '{}'", self.id, name),
+            None => format!("task #{}", self.id),
+        }
+    }
+
+    fn info(&self) -> TaskInfo {
+        TaskInfo {
+            id: self.id,
+            name: self.name.clone(),
+            state: match self.state.load(Ordering::SeqCst) {
+                STATE_SCHEDULED => TaskState::Scheduled,
+                STATE_RUNNING | STATE_NOTIFIED => TaskState::Running,
+                _ => TaskState::Idle,
+            },
+            poll_count: self.poll_count.load(Ordering::Relaxed),
+            poll_time: Duration::from_nanos(self.poll_nanos.load(Ordering::Relaxed)),
+            slow_polls: self.slow_polls.load(Ordering::Relaxed),
+            age: self.spawned_at.elapsed(),
+        }
+    }
+
+    /// 取消任务：标记后重新调度，由工作线程丢弃其future
+    pub(crate) fn abort(self: &Arc<Self>) {
+        self.aborted.store(true, Ordering::SeqCst);
+        ArcWake::wake_by_ref(self);
+    }
+}
+
+impl Drop for Task {
+    /// 未完成的任务不再被任何waker引用时也视为结束，避免join一直等待
+    fn drop(&mut self) {
+        self.shared.task_finished(self.id);
+    }
+}
+
+impl ArcWake for Task {
+    /// 已在就绪队列中的任务不重复入队，正在执行的任务只做标记
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        if arc_self.shared.closed.load(Ordering::SeqCst) {  // 运行时关闭后不再调度
+            return;
+        }
+        let mut state = arc_self.state.load(Ordering::SeqCst);
+        loop {
+            let next = match state {
+                STATE_IDLE => STATE_SCHEDULED,
+                STATE_RUNNING => STATE_NOTIFIED,
+                _ => return,  // 已调度或已标记
+            };
+            match arc_self.state.compare_exchange_weak(
+                state, next, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => break,
+                Err(current) => state = current,
+            }
+        }
+        if state == STATE_IDLE {
+            arc_self.schedule();
+        }
+    }
+}
+
+/// 向运行时提交任务的句柄，可以克隆后在多个线程中使用
+#[derive(Clone)]
+pub struct Spawner {
+    shared: Arc<Shared>,
+}
+
+impl Spawner {
+    /// 提交异步任务，返回可等待任务结果的句柄，就绪队列已满或运行时已关闭时返回错误
+    pub fn spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>, SpawnError>
+        where F: Future + Send + 'static,
+              F::Output: Send + 'static
+    {
+        self.spawn_task(None, future)
+    }
+    /// 提交带名称的异步任务，名称会出现在慢推进警告和任务快照中
+    pub fn spawn_named<S, F>(&self, name: S, future: F) -> Result<JoinHandle<F::Output>, SpawnError>
+        where S: Into<String>,
+              F: Future + Send + 'static,
+              F::Output: Send + 'static
+    {
+        self.spawn_task(Some(name.into()), future)
+    }
+
+    fn spawn_task<F>(&self, name: Option<String>, future: F) -> Result<JoinHandle<F::Output>, SpawnError>
+        where F: Future + Send + 'static,
+              F::Output: Send + 'static
+    {
+        let shared = &self.shared;
+        if shared.closed.load(Ordering::SeqCst) {
+            return Err(SpawnError::Shutdown);
+        }
+        if !shared.try_reserve_slot() {
+            return Err(SpawnError::QueueFull);
+        }
+        let id = shared.next_task_id.fetch_add(1, Ordering::Relaxed);
+        let finished_shared = Arc::downgrade(shared);
+        let (future, mut handle) = join_pair(future, Box::new(move || {
+            if let Some(shared) = finished_shared.upgrade() {
+                shared.task_finished(id);
+            }
+        }));
+        let task = Arc::new(Task {
+            id,
+            name,
+            future: Mutex::new(Some(future.boxed())),
+            aborted: AtomicBool::new(false),
+            state: AtomicU8::new(STATE_SCHEDULED),
+            poll_count: AtomicU64::new(0),
+            poll_nanos: AtomicU64::new(0),
+            slow_polls: AtomicU64::new(0),
+            spawned_at: Instant::now(),
+            shared: shared.clone(),
+        });
+        handle.set_task(Arc::downgrade(&task));
+        shared.tasks.lock()
+            .expect("unable to lock tasks")
+            .insert(id, Arc::downgrade(&task));
+        let _ = shared.task_sender.send(Some(task));
+        Ok(handle)
+    }
+}
+
+/// 多线程异步运行时，由若干工作线程从就绪队列中取出任务并推进
+pub struct Runtime {
+    spawner: Spawner,
+    threads: Vec<thread::JoinHandle<()>>,  // 工作线程的句柄，释放运行时时等待其退出
+}
+
+impl Runtime {
+    /// 以默认配置创建运行时，工作线程数为CPU核数
+    pub fn new() -> Runtime {
+        Builder::new().build()
+    }
+    /// 提交异步任务，返回可等待任务结果的句柄，就绪队列已满或运行时已关闭时返回错误
+    pub fn spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>, SpawnError>
+        where F: Future + Send + 'static,
+              F::Output: Send + 'static
+    {
+        self.spawner.spawn(future)
+    }
+    /// 提交带名称的异步任务，见`Spawner::spawn_named`
+    pub fn spawn_named<S, F>(&self, name: S, future: F) -> Result<JoinHandle<F::Output>, SpawnError>
+        where S: Into<String>,
+              F: Future + Send + 'static,
+              F::Output: Send + 'static
+    {
+        self.spawner.spawn_named(name, future)
+    }
+    /// 在当前线程上运行future直到完成，future中可以通过`spawner`向本运行时提交任务
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(future)
+    }
+    /// 获取可在其他线程或任务中提交任务的句柄
+    pub fn spawner(&self) -> Spawner {
+        self.spawner.clone()
+    }
+    /// 阻塞直到所有已提交的任务完成
+    ///
+    /// 不要在本运行时的任务中调用，否则会因等待自身而死锁
+    pub fn join(&self) {
+        let shared = &self.spawner.shared;
+        let mut tasks = shared.tasks.lock()
+            .expect("unable to lock tasks");
+        while !tasks.is_empty() {
+            tasks = shared.idle_condvar.wait(tasks)
+                .expect("unable to wait for tasks");
+        }
+    }
+    /// 工作线程数
+    pub fn num_threads(&self) -> usize {
+        self.threads.len()
+    }
+    /// 获取所有未完成任务的快照，按任务编号排序，用于排查卡住的任务
+    pub fn dump(&self) -> Vec<TaskInfo> {
+        let tasks: Vec<_> = self.spawner.shared.tasks.lock()
+            .expect("unable to lock tasks")
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect();
+        let mut infos: Vec<_> = tasks.iter().map(|task| task.info()).collect();
+        infos.sort_by_key(|info| info.id);
+        infos
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Runtime {
+        Runtime::new()
+    }
+}
+
+impl Drop for Runtime {
+    /// 通知工作线程退出并等待，尚未完成的任务被丢弃
+    fn drop(&mut self) {
+        let shared = &self.spawner.shared;
+        shared.closed.store(true, Ordering::SeqCst);
+        for _ in 0..self.threads.len() {
+            let _ = shared.task_sender.send(None);
+        }
+        for handle in self.threads.drain(..) {
+            let _ = handle.join();
+        }
+        // 丢弃仍在队列中的任务，打破任务与共享状态之间的循环引用
+        {
+            let ready_queue = shared.ready_queue.lock()
+                .expect("unable to lock ready queue");
+            while ready_queue.try_recv().is_ok() {}
+        }
+        // 被其他地方的waker引用的任务也要释放其future，使JoinHandle得到Cancelled
+        let tasks: Vec<_> = shared.tasks.lock()
+            .expect("unable to lock tasks")
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for task in tasks {
+            let future = task.future.lock().unwrap().take();
+            drop(future);  // 在锁外丢弃，future的析构可能唤醒其他任务
+        }
+    }
+}
+
+/// 运行时构造器，用于配置工作线程数、就绪队列容量和线程名称
+#[derive(Clone, Default)]
+pub struct Builder {
+    num_threads: Option<usize>,  // 工作线程数
+    queue_capacity: Option<usize>,  // 就绪队列容量
+    thread_name: Option<String>,  // 工作线程名称
+    slow_poll_threshold: Option<Duration>,  // 慢推进阈值
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder {
+            num_threads: None,
+            queue_capacity: None,
+            thread_name: None,
+            slow_poll_threshold: None,
+        }
+    }
+    // 配置工作线程数，默认为CPU核数
+    pub fn num_threads(mut self, num_threads: usize) -> Builder {
+        assert!(num_threads > 0);
+        self.num_threads = Some(num_threads);
+        self
+    }
+    // 配置就绪队列容量，默认10000
+    pub fn queue_capacity(mut self, capacity: usize) -> Builder {
+        assert!(capacity > 0);
+        self.queue_capacity = Some(capacity);
+        self
+    }
+    // 配置工作线程名称
+    pub fn thread_name(mut self, name: String) -> Builder {
+        self.thread_name = Some(name);
+        self
+    }
+    // 配置慢推进阈值，任务单次推进超过该时间时输出警告，默认不检测
+    pub fn slow_poll_threshold(mut self, threshold: Duration) -> Builder {
+        self.slow_poll_threshold = Some(threshold);
+        self
+    }
+    // 创建运行时并启动工作线程
+    pub fn build(self) -> Runtime {
+        let num_threads = self.num_threads
+            .unwrap_or_else(num_cpus::get);
+        let (task_sender, ready_queue) = channel();
+        let shared = Arc::new(Shared {
+            task_sender,
+            ready_queue: Mutex::new(ready_queue),
+            queue_capacity: self.queue_capacity.unwrap_or(DEFAULT_QUEUE_CAPACITY),
+            queued_count: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            tasks: Mutex::new(HashMap::new()),
+            next_task_id: AtomicU64::new(0),
+            idle_condvar: Condvar::new(),
+            slow_poll_threshold: self.slow_poll_threshold,
+        });
+        let threads = (0..num_threads)
+            .map(|_| {
+                let mut builder = thread::Builder::new();
+                if let Some(ref name) = self.thread_name {
+                    builder = builder.name(name.clone());
+                }
+                let shared = shared.clone();
+                builder.spawn(move || run_worker(&shared))
+                    .expect("unable to spawn runtime worker")
+            })
+            .collect();
+        Runtime {
+            spawner: Spawner { shared },
+            threads,
+        }
+    }
+}
+
+/// 工作线程循环：从就绪队列取出任务并推进，收到None时退出
+fn run_worker(shared: &Shared) {
+    loop {
+        let message = {
+            let ready_queue = shared.ready_queue.lock()
+                .expect("unable to lock ready queue");
+            ready_queue.recv()
+        };
+        match message {
+            Ok(Some(task)) => {
+                shared.queued_count.fetch_sub(1, Ordering::SeqCst);
+                task.poll();
+            }
+            _ => break,
+        }
+    }
+}
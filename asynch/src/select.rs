@@ -0,0 +1,65 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// `select`的结果，表示先完成的是哪一个future
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),  // 第一个future先完成
+    Right(B),  // 第二个future先完成
+}
+
+/// 同时等待两个future，由`select`创建
+pub struct Select<A, B> {
+    a: Pin<Box<A>>,
+    b: Pin<Box<B>>,
+}
+
+/// 同时等待两个future，返回先完成的那个的结果，另一个随之被丢弃
+///
+/// 两个future同时就绪时优先返回第一个
+pub fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select {
+        a: Box::pin(a),
+        b: Box::pin(b),
+    }
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.a.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        self.b.as_mut().poll(cx).map(Either::Right)
+    }
+}
+
+/// 同时等待一组future，由`select_all`创建
+pub struct SelectAll<F> {
+    futures: Vec<Pin<Box<F>>>,
+}
+
+/// 同时等待一组future，返回先完成的那个的结果及其下标，其余的随之被丢弃
+///
+/// `futures`为空时会引发恐慌
+pub fn select_all<I>(futures: I) -> SelectAll<I::Item>
+    where I: IntoIterator,
+          I::Item: Future
+{
+    let futures: Vec<_> = futures.into_iter().map(Box::pin).collect();
+    assert!(!futures.is_empty(), "select_all requires at least one future");
+    SelectAll { futures }
+}
+
+impl<F: Future> Future for SelectAll<F> {
+    type Output = (F::Output, usize);
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        for (index, future) in self.futures.iter_mut().enumerate() {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready((output, index));
+            }
+        }
+        Poll::Pending
+    }
+}
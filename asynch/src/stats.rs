@@ -0,0 +1,46 @@
+use std::fmt;
+use std::time::Duration;
+
+/// 任务当前所处的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Scheduled,  // 在就绪队列中等待执行
+    Running,  // 正在被工作线程推进
+    Idle,  // 等待被唤醒，如等待定时器、I/O或其他任务
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TaskState::Scheduled => write!(f, "scheduled"),
+            TaskState::Running => write!(f, "running"),
+            TaskState::Idle => write!(f, "idle"),
+        }
+    }
+}
+
+/// 某一时刻单个未完成任务的快照，由`Runtime::dump`返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    pub id: u64,  // 任务编号，按提交顺序递增
+    pub name: Option<String>,  // 通过`spawn_named`指定的名称
+    pub state: TaskState,  // 当前状态
+    pub poll_count: u64,  // 被推进的次数
+    pub poll_time: Duration,  // 所有推进累计花费的时间
+    pub slow_polls: u64,  // 超过慢推进阈值的次数
+    pub age: Duration,  // 提交以来经过的时间
+}
+
+impl fmt::Display for TaskInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "task #{} '{}'", self.id, name)?,
+            None => write!(f, "task #{}", self.id)?,
+        }
+        write!(f, ": {}, {} polls in {:?}", self.state, self.poll_count, self.poll_time)?;
+        if self.slow_polls > 0 {
+            write!(f, " ({} slow)", self.slow_polls)?;
+        }
+        write!(f, ", alive for {:?}", self.age)
+    }
+}
@@ -0,0 +1,11 @@
+//! 异步任务之间的通信和同步原语，等待时挂起任务而不阻塞工作线程
+
+mod error;
+pub mod mpsc;
+mod mutex;
+pub mod oneshot;
+mod semaphore;
+
+pub use self::error::{RecvError, SendError, TryAcquireError, TryRecvError, TrySendError};
+pub use self::mutex::{Mutex, MutexGuard};
+pub use self::semaphore::{Acquire, Semaphore, SemaphorePermit};
@@ -0,0 +1,243 @@
+//! 多生产者、单消费者的通道，分为有界和无界两种
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll, Waker};
+
+use futures::future::poll_fn;
+use futures::stream::Stream;
+
+use super::{SendError, TryRecvError, TrySendError};
+
+struct State<T> {
+    queue: VecDeque<T>,  // 尚未取走的消息
+    capacity: Option<usize>,  // 通道容量，None表示无界
+    senders: usize,  // 存活的发送端数量
+    receiver_alive: bool,  // 接收端是否存活
+    recv_waker: Option<Waker>,  // 等待消息的接收端
+    send_wakers: Vec<Waker>,  // 等待空位的发送端
+}
+
+/// 发送端与接收端共享的通道
+struct Chan<T> {
+    state: StdMutex<State<T>>,
+}
+
+impl<T> Chan<T> {
+    fn new(capacity: Option<usize>) -> Arc<Chan<T>> {
+        Arc::new(Chan {
+            state: StdMutex::new(State {
+                queue: VecDeque::new(),
+                capacity,
+                senders: 1,
+                receiver_alive: true,
+                recv_waker: None,
+                send_wakers: Vec::new(),
+            }),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State<T>> {
+        self.state.lock()
+            .expect("unable to lock channel")
+    }
+
+    /// 通道有空位时放入消息，`waker`为Some时在通道已满时登记等待
+    fn push(&self, value: T, waker: Option<&Waker>) -> Result<(), TrySendError<T>> {
+        let recv_waker = {
+            let mut state = self.lock();
+            if !state.receiver_alive {
+                return Err(TrySendError::Closed(value));
+            }
+            if state.capacity.is_some_and(|capacity| state.queue.len() >= capacity) {
+                if let Some(waker) = waker {
+                    state.send_wakers.push(waker.clone());
+                }
+                return Err(TrySendError::Full(value));
+            }
+            state.queue.push_back(value);
+            state.recv_waker.take()
+        };
+        if let Some(waker) = recv_waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    fn poll_recv(&self, waker: Option<&Waker>) -> Poll<Result<T, TryRecvError>> {
+        let (value, send_wakers) = {
+            let mut state = self.lock();
+            match state.queue.pop_front() {
+                // 取走消息后唤醒所有等待空位的发送端，由它们重新竞争，避免被取消的发送端吞掉唤醒
+                Some(value) => (value, std::mem::take(&mut state.send_wakers)),
+                // 所有发送端已关闭，或接收端已调用close且消息已取完
+                None if state.senders == 0 || !state.receiver_alive => {
+                    return Poll::Ready(Err(TryRecvError::Closed));
+                }
+                None => {
+                    if let Some(waker) = waker {
+                        state.recv_waker = Some(waker.clone());
+                    }
+                    return Poll::Pending;
+                }
+            }
+        };
+        for waker in send_wakers {
+            waker.wake();
+        }
+        Poll::Ready(Ok(value))
+    }
+
+    fn add_sender(&self) {
+        self.lock().senders += 1;
+    }
+
+    fn drop_sender(&self) {
+        let recv_waker = {
+            let mut state = self.lock();
+            state.senders -= 1;
+            if state.senders > 0 {
+                return;
+            }
+            state.recv_waker.take()
+        };
+        if let Some(waker) = recv_waker {  // 最后一个发送端关闭时通知接收端
+            waker.wake();
+        }
+    }
+}
+
+/// 创建容量为`capacity`的有界通道，通道已满时发送端等待
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "channel capacity must be non-zero");
+    let chan = Chan::new(Some(capacity));
+    (Sender { chan: chan.clone() }, Receiver { chan })
+}
+
+/// 创建无界通道，发送总是立即完成
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, Receiver<T>) {
+    let chan = Chan::new(None);
+    (UnboundedSender { chan: chan.clone() }, Receiver { chan })
+}
+
+/// 有界通道的发送端，可以克隆
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> Sender<T> {
+    /// 发送消息，通道已满时等待空位，接收端已关闭时返回消息
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut value = Some(value);
+        poll_fn(|cx| {
+            let message = value.take().expect("send polled after completion");
+            match self.chan.push(message, Some(cx.waker())) {
+                Ok(()) => Poll::Ready(Ok(())),
+                Err(TrySendError::Closed(message)) => Poll::Ready(Err(SendError(message))),
+                Err(TrySendError::Full(message)) => {
+                    value = Some(message);
+                    Poll::Pending
+                }
+            }
+        }).await
+    }
+    /// 不等待地发送消息
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.chan.push(value, None)
+    }
+    /// 接收端是否已关闭
+    pub fn is_closed(&self) -> bool {
+        !self.chan.lock().receiver_alive
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.chan.add_sender();
+        Sender { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.chan.drop_sender();
+    }
+}
+
+/// 无界通道的发送端，可以克隆
+pub struct UnboundedSender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> UnboundedSender<T> {
+    /// 发送消息，接收端已关闭时返回消息
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.chan.push(value, None)
+            .map_err(|error| SendError(error.into_inner()))
+    }
+    /// 接收端是否已关闭
+    pub fn is_closed(&self) -> bool {
+        !self.chan.lock().receiver_alive
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> UnboundedSender<T> {
+        self.chan.add_sender();
+        UnboundedSender { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        self.chan.drop_sender();
+    }
+}
+
+/// 通道的接收端，也实现了`Stream`
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// 等待下一条消息，所有发送端关闭或接收端已关闭，且消息已取完时返回None
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+    /// 不等待地取出消息
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        match self.chan.poll_recv(None) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(TryRecvError::Empty),
+        }
+    }
+    /// 轮询下一条消息
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.chan.poll_recv(Some(cx.waker())).map(Result::ok)
+    }
+    /// 关闭接收端，之后的发送都会失败，已在通道中的消息仍可取出
+    pub fn close(&mut self) {
+        let send_wakers = {
+            let mut state = self.chan.lock();
+            state.receiver_alive = false;
+            std::mem::take(&mut state.send_wakers)
+        };
+        for waker in send_wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
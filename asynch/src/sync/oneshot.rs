@@ -0,0 +1,108 @@
+//! 只传递一个值的通道
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll, Waker};
+
+use super::RecvError;
+
+struct State<T> {
+    value: Option<T>,  // 已发送但尚未取走的值
+    sender_dropped: bool,  // 发送端是否已被使用或丢弃
+    receiver_dropped: bool,  // 接收端是否已被丢弃
+    waker: Option<Waker>,  // 等待值的接收端
+}
+
+/// 创建oneshot通道
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let state = Arc::new(StdMutex::new(State {
+        value: None,
+        sender_dropped: false,
+        receiver_dropped: false,
+        waker: None,
+    }));
+    (Sender { state: state.clone() }, Receiver { state })
+}
+
+/// oneshot通道的发送端
+pub struct Sender<T> {
+    state: Arc<StdMutex<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// 发送值，接收端已被丢弃时将值原样返回
+    pub fn send(self, value: T) -> Result<(), T> {
+        let mut state = self.state.lock()
+            .expect("unable to lock oneshot");
+        if state.receiver_dropped {
+            return Err(value);
+        }
+        state.value = Some(value);
+        Ok(())  // 在Drop中唤醒接收端
+    }
+    /// 接收端是否已被丢弃
+    pub fn is_closed(&self) -> bool {
+        self.state.lock()
+            .expect("unable to lock oneshot")
+            .receiver_dropped
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.state.lock()
+                .expect("unable to lock oneshot");
+            state.sender_dropped = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// oneshot通道的接收端，本身是一个Future
+///
+/// 发送端未发送就被丢弃时得到`Err(RecvError)`
+pub struct Receiver<T> {
+    state: Arc<StdMutex<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// 不等待地取出值，尚未发送时返回None
+    pub fn try_recv(&mut self) -> Option<Result<T, RecvError>> {
+        let mut state = self.state.lock()
+            .expect("unable to lock oneshot");
+        match state.value.take() {
+            Some(value) => Some(Ok(value)),
+            None if state.sender_dropped => Some(Err(RecvError)),
+            None => None,
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock()
+            .expect("unable to lock oneshot");
+        match state.value.take() {
+            Some(value) => Poll::Ready(Ok(value)),
+            None if state.sender_dropped => Poll::Ready(Err(RecvError)),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.state.lock()
+            .expect("unable to lock oneshot")
+            .receiver_dropped = true;
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll, Waker};
+
+use super::TryAcquireError;
+
+/// 排队等待许可的任务，许可直接分配给队首的等待者，保证先来先得
+struct Waiter {
+    assigned: AtomicBool,  // 是否已分配到许可，只在持有信号量的锁时修改
+    waker: StdMutex<Option<Waker>>,
+}
+
+struct SemaphoreState {
+    permits: usize,  // 可用的许可数
+    waiters: VecDeque<Arc<Waiter>>,  // 按先后顺序排队的等待者
+}
+
+/// 异步信号量，没有许可时挂起任务而不是阻塞线程
+pub struct Semaphore {
+    state: StdMutex<SemaphoreState>,
+}
+
+impl Semaphore {
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            state: StdMutex::new(SemaphoreState {
+                permits,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+    /// 当前可用的许可数
+    pub fn available_permits(&self) -> usize {
+        self.state.lock()
+            .expect("unable to lock semaphore")
+            .permits
+    }
+    /// 等待并获取一个许可，许可在返回的SemaphorePermit释放时归还
+    pub fn acquire(&self) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            waiter: None,
+        }
+    }
+    /// 不等待地获取一个许可，有任务在排队时也会失败
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        let mut state = self.state.lock()
+            .expect("unable to lock semaphore");
+        if state.permits > 0 && state.waiters.is_empty() {
+            state.permits -= 1;
+            Ok(SemaphorePermit { semaphore: self })
+        } else {
+            Err(TryAcquireError)
+        }
+    }
+    /// 增加许可，分配给排队的等待者
+    pub fn add_permits(&self, count: usize) {
+        let wakers = {
+            let mut state = self.state.lock()
+                .expect("unable to lock semaphore");
+            state.permits += count;
+            assign_permits(&mut state)
+        };
+        for waker in wakers {  // 在锁外唤醒
+            waker.wake();
+        }
+    }
+}
+
+/// 将可用的许可依次分配给队首的等待者，返回需要唤醒的waker
+fn assign_permits(state: &mut SemaphoreState) -> Vec<Waker> {
+    let mut wakers = Vec::new();
+    while state.permits > 0 {
+        let waiter = match state.waiters.pop_front() {
+            Some(waiter) => waiter,
+            None => break,
+        };
+        state.permits -= 1;
+        waiter.assigned.store(true, Ordering::SeqCst);
+        wakers.extend(waiter.waker.lock().unwrap().take());
+    }
+    wakers
+}
+
+/// 信号量的许可，释放时归还
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl SemaphorePermit<'_> {
+    /// 不归还许可，使信号量永久减少一个许可
+    pub fn forget(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(1);
+    }
+}
+
+/// `Semaphore::acquire`返回的future
+///
+/// 排队期间被丢弃时退出队列，已分配的许可会转交给下一个等待者
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    waiter: Option<Arc<Waiter>>,  // 已进入等待队列时为Some
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SemaphorePermit<'a>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        let mut state = semaphore.state.lock()
+            .expect("unable to lock semaphore");
+        match self.waiter {
+            Some(ref waiter) => {
+                if !waiter.assigned.load(Ordering::SeqCst) {
+                    *waiter.waker.lock().unwrap() = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+            None => {
+                if state.permits == 0 || !state.waiters.is_empty() {
+                    let waiter = Arc::new(Waiter {
+                        assigned: AtomicBool::new(false),
+                        waker: StdMutex::new(Some(cx.waker().clone())),
+                    });
+                    state.waiters.push_back(waiter.clone());
+                    self.waiter = Some(waiter);
+                    return Poll::Pending;
+                }
+                state.permits -= 1;
+            }
+        }
+        self.waiter = None;
+        Poll::Ready(SemaphorePermit { semaphore })
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let waiter = match self.waiter.take() {
+            Some(waiter) => waiter,
+            None => return,
+        };
+        let wakers = {
+            let mut state = self.semaphore.state.lock()
+                .expect("unable to lock semaphore");
+            if waiter.assigned.load(Ordering::SeqCst) {  // 已分配的许可转交给其他等待者
+                state.permits += 1;
+                assign_permits(&mut state)
+            } else {
+                state.waiters.retain(|queued| !Arc::ptr_eq(queued, &waiter));
+                Vec::new()
+            }
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
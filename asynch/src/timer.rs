@@ -0,0 +1,296 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, OnceLock, Weak};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::stream::Stream;
+
+use super::Elapsed;
+
+/// 在指定时间后完成的Future，所有TimerFuture共用一个定时线程
+pub struct TimerFuture {
+    shared_state: Arc<Mutex<SharedState>>,  // 通过Arc<Mutex<..>>在定时线程和Future之间共享数据
+    deadline: Instant,  // 到期时间
+    seq: Option<u64>,  // 在定时线程中登记的序号，创建时已到期则为None
+}
+
+/// 在future和定时线程之间共享状态
+struct SharedState {
+    completed: bool, // 睡眠是否完成
+    waker: Option<Waker>, // TimerFuture
+}
+
+impl Future for TimerFuture {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared_state = self.shared_state.lock().unwrap();
+        if shared_state.completed {
+            Poll::Ready(())
+        } else {
+            shared_state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl TimerFuture {
+    /// 创建在`duration`后完成的定时器
+    pub fn new(duration: Duration) -> Self {
+        TimerFuture::at(Instant::now() + duration)
+    }
+    /// 创建在`deadline`时刻完成的定时器
+    pub fn at(deadline: Instant) -> Self {
+        let shared_state = Arc::new(Mutex::new(SharedState {
+            completed: deadline <= Instant::now(),
+            waker: None,
+        }));
+        let seq = if shared_state.lock().unwrap().completed {
+            None
+        } else {
+            Some(driver().register(deadline, Arc::downgrade(&shared_state)))
+        };
+        TimerFuture { shared_state, deadline, seq }
+    }
+    /// 到期时间
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+impl Drop for TimerFuture {
+    /// 未到期就被丢弃的定时器从定时线程中注销
+    fn drop(&mut self) {
+        if let Some(seq) = self.seq {
+            if !self.shared_state.lock().unwrap().completed {
+                driver().cancel(seq);
+            }
+        }
+    }
+}
+
+/// 定时线程中登记的定时器数，包括已被丢弃但尚未清理的项，用于诊断
+pub fn pending_timers() -> usize {
+    match DRIVER.get() {
+        Some(driver) => driver.state.lock()
+            .expect("unable to lock timer driver")
+            .entries.len(),
+        None => 0,
+    }
+}
+
+/// 等待`duration`
+pub fn sleep(duration: Duration) -> TimerFuture {
+    TimerFuture::new(duration)
+}
+
+/// 等待到`deadline`时刻
+pub fn sleep_until(deadline: Instant) -> TimerFuture {
+    TimerFuture::at(deadline)
+}
+
+/// 定时器中的一项，按到期时间排序，时间相同时按登记顺序
+struct TimerEntry {
+    deadline: Instant,
+    seq: u64,
+    shared_state: Weak<Mutex<SharedState>>,  // TimerFuture已被丢弃时无需唤醒
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &TimerEntry) -> bool {
+        self.deadline == other.deadline && self.seq == other.seq
+    }
+}
+
+impl Eq for TimerEntry {}
+
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &TimerEntry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    /// BinaryHeap为大顶堆，反转比较结果使最早到期的一项位于堆顶
+    fn cmp(&self, other: &TimerEntry) -> Ordering {
+        (other.deadline, other.seq).cmp(&(self.deadline, self.seq))
+    }
+}
+
+struct DriverState {
+    entries: BinaryHeap<TimerEntry>,  // 尚未到期的定时器
+    cancelled: HashSet<u64>,  // 已被丢弃但仍在堆中的定时器序号
+    next_seq: u64,  // 下一项的登记序号
+}
+
+/// 定时驱动，由一个后台线程在定时器到期时唤醒对应的任务
+struct Driver {
+    state: Mutex<DriverState>,
+    condvar: Condvar,  // 登记了更早到期的定时器时唤醒定时线程
+}
+
+static DRIVER: OnceLock<Driver> = OnceLock::new();
+
+/// 获取全局定时驱动，首次使用时启动定时线程
+fn driver() -> &'static Driver {
+    DRIVER.get_or_init(|| {
+        thread::Builder::new()
+            .name("asynch-timer".to_owned())
+            .spawn(|| driver().run())  // 定时线程在初始化完成后才能取得驱动
+            .expect("unable to spawn timer thread");
+        Driver {
+            state: Mutex::new(DriverState {
+                entries: BinaryHeap::new(),
+                cancelled: HashSet::new(),
+                next_seq: 0,
+            }),
+            condvar: Condvar::new(),
+        }
+    })
+}
+
+impl Driver {
+    /// 登记定时器，返回其序号
+    fn register(&self, deadline: Instant, shared_state: Weak<Mutex<SharedState>>) -> u64 {
+        let mut state = self.state.lock()
+            .expect("unable to lock timer driver");
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        let earliest = state.entries.peek()
+            .is_none_or(|entry| deadline < entry.deadline);
+        state.entries.push(TimerEntry { deadline, seq, shared_state });
+        if earliest {  // 只有最早到期时间改变时才需要唤醒定时线程
+            self.condvar.notify_one();
+        }
+        seq
+    }
+
+    /// 注销定时器，被注销的项多于有效项时才从堆中清理，避免每次丢弃都重建堆
+    fn cancel(&self, seq: u64) {
+        let mut state = self.state.lock()
+            .expect("unable to lock timer driver");
+        state.cancelled.insert(seq);
+        let live = state.entries.len().saturating_sub(state.cancelled.len());
+        if state.cancelled.len() > live {
+            let DriverState { ref mut entries, ref mut cancelled, .. } = *state;
+            entries.retain(|entry| !cancelled.contains(&entry.seq));
+            cancelled.clear();  // 定时线程已取出的项也一并忘记
+        }
+    }
+
+    /// 定时线程循环：取出所有到期的定时器并在锁外唤醒
+    fn run(&self) {
+        let mut state = self.state.lock()
+            .expect("unable to lock timer driver");
+        loop {
+            let now = Instant::now();
+            let mut expired = Vec::new();
+            while state.entries.peek().is_some_and(|entry| entry.deadline <= now) {
+                let entry = state.entries.pop().expect("timer entry disappeared");
+                if !state.cancelled.remove(&entry.seq) {
+                    expired.push(entry);
+                }
+            }
+            if !expired.is_empty() {
+                drop(state);
+                for entry in expired {
+                    fire(entry);
+                }
+                state = self.state.lock()
+                    .expect("unable to lock timer driver");
+                continue;
+            }
+            state = match state.entries.peek() {
+                Some(entry) => {
+                    let timeout = entry.deadline - now;
+                    self.condvar.wait_timeout(state, timeout)
+                        .expect("unable to wait for timer").0
+                }
+                None => self.condvar.wait(state)
+                    .expect("unable to wait for timer"),
+            };
+        }
+    }
+}
+
+fn fire(entry: TimerEntry) {
+    if let Some(shared_state) = entry.shared_state.upgrade() {
+        let mut shared_state = shared_state.lock().unwrap();
+        shared_state.completed = true;
+        if let Some(waker) = shared_state.waker.take() {
+            waker.wake()
+        }
+    }
+}
+
+/// 周期定时器，由`interval`创建
+///
+/// 每隔固定时间到期一次；处理不及时错过的周期会被跳过，而不是连续补发
+pub struct Interval {
+    period: Duration,  // 周期
+    timer: TimerFuture,  // 下一次到期的定时器
+}
+
+/// 创建周期为`period`的定时器，第一次在一个周期后到期
+pub fn interval(period: Duration) -> Interval {
+    assert!(period > Duration::from_secs(0), "interval period must be non-zero");
+    Interval {
+        period,
+        timer: TimerFuture::new(period),
+    }
+}
+
+impl Interval {
+    /// 等待下一次到期，返回本次计划的到期时间
+    pub fn tick(&mut self) -> impl Future<Output = Instant> + '_ {
+        futures::future::poll_fn(move |cx| self.poll_tick(cx))
+    }
+    /// 轮询下一次到期
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        if Pin::new(&mut self.timer).poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        let deadline = self.timer.deadline();
+        let now = Instant::now();
+        let mut next = deadline + self.period;
+        if next <= now {  // 已经错过下一个周期，从现在重新计时
+            next = now + self.period;
+        }
+        self.timer = TimerFuture::at(next);
+        Poll::Ready(deadline)
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        self.get_mut().poll_tick(cx).map(Some)
+    }
+}
+
+/// 为future设置超时，由`timeout`创建
+pub struct Timeout<F> {
+    future: Pin<Box<F>>,
+    timer: TimerFuture,
+}
+
+/// 在`duration`内等待future完成，超时则返回`Err(Elapsed)`并丢弃future
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        timer: TimerFuture::new(duration),
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(&mut self.timer).poll(cx).map(|()| Err(Elapsed))
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use asynch::{block_on, select, select_all, sleep, Builder, Either};
+
+/// 被丢弃时记录下来，用于确认future的析构已经执行
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn abort_drops_pending_future() {
+    let runtime = Builder::new().num_threads(2).build();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let handle = runtime.spawn(async move {
+        let _flag = flag;
+        sleep(Duration::from_secs(60)).await;
+    }).unwrap();
+    handle.abort();
+    let err = runtime.block_on(handle).unwrap_err();
+    assert!(err.is_cancelled());
+    assert!(dropped.load(Ordering::SeqCst));
+    runtime.join();  // 被取消的任务不再计入未完成任务
+}
+
+#[test]
+fn abort_after_completion_keeps_result() {
+    let runtime = Builder::new().num_threads(1).build();
+    let handle = runtime.spawn(async { 5 }).unwrap();
+    let abort = handle.abort_handle();
+    runtime.join();
+    abort.abort();
+    assert_eq!(5, runtime.block_on(handle).unwrap());
+}
+
+#[test]
+fn abort_from_inside_task_stops_it() {
+    let runtime = Builder::new().num_threads(1).build();
+    let polls = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = {
+        let polls = polls.clone();
+        runtime.spawn(async move {
+            let abort: asynch::AbortHandle = rx.recv().unwrap();
+            abort.abort();
+            polls.fetch_add(1, Ordering::SeqCst);
+            sleep(Duration::from_millis(10)).await;
+            polls.fetch_add(1, Ordering::SeqCst);  // 不应执行到这里
+        }).unwrap()
+    };
+    tx.send(handle.abort_handle()).unwrap();
+    assert!(runtime.block_on(handle).unwrap_err().is_cancelled());
+    assert_eq!(1, polls.load(Ordering::SeqCst));
+}
+
+#[test]
+fn dropping_task_group_cancels_children() {
+    let runtime = Builder::new().num_threads(2).build();
+    let group = runtime.task_group();
+    let flags: Vec<_> = (0..5).map(|_| Arc::new(AtomicBool::new(false))).collect();
+    let handles: Vec<_> = flags.iter()
+        .map(|dropped| {
+            let flag = DropFlag(dropped.clone());
+            group.spawn(async move {
+                let _flag = flag;
+                sleep(Duration::from_secs(60)).await;
+            }).unwrap()
+        })
+        .collect();
+    let finished = group.spawn(async { 1 }).unwrap();
+    assert_eq!(1, runtime.block_on(finished).unwrap());
+    drop(group);
+    for handle in handles {
+        assert!(runtime.block_on(handle).unwrap_err().is_cancelled());
+    }
+    assert!(flags.iter().all(|dropped| dropped.load(Ordering::SeqCst)));
+}
+
+#[test]
+fn cancelling_parent_cancels_group_children() {
+    let runtime = Builder::new().num_threads(2).build();
+    let spawner = runtime.spawner();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = futures::channel::oneshot::channel();
+    let parent = {
+        let flag = DropFlag(dropped.clone());
+        runtime.spawn(async move {
+            let group = spawner.task_group();
+            let child = group.spawn(async move {
+                let _flag = flag;
+                sleep(Duration::from_secs(60)).await;
+            }).unwrap();
+            tx.send(child).unwrap();
+            sleep(Duration::from_secs(60)).await;
+        }).unwrap()
+    };
+    let child = runtime.block_on(rx).unwrap();
+    parent.abort();
+    assert!(runtime.block_on(parent).unwrap_err().is_cancelled());
+    assert!(runtime.block_on(child).unwrap_err().is_cancelled());
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn select_returns_first_and_drops_loser() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let slow = async move {
+        let _flag = flag;
+        sleep(Duration::from_secs(60)).await;
+        "slow"
+    };
+    let fast = async {
+        sleep(Duration::from_millis(10)).await;
+        1
+    };
+    assert_eq!(Either::Right(1), block_on(select(slow, fast)));
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn select_all_returns_index_of_winner() {
+    let futures = [30u64, 10, 20].iter().map(|&millis| async move {
+        sleep(Duration::from_millis(millis)).await;
+        millis
+    });
+    assert_eq!((10, 1), block_on(select_all(futures)));
+}
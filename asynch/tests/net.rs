@@ -0,0 +1,88 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+use asynch::{block_on, timeout, AsyncTcpListener, AsyncTcpStream, Builder};
+
+#[test]
+fn echo_round_trip_between_tasks() {
+    let runtime = Builder::new().num_threads(2).build();
+    let listener = AsyncTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = runtime.spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1024];
+        let mut echoed = 0;
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            if n == 0 {
+                return echoed;
+            }
+            stream.write_all(&buf[..n]).await.unwrap();
+            echoed += n;
+        }
+    }).unwrap();
+    let client = runtime.spawn(async move {
+        let mut stream = AsyncTcpStream::connect(addr).await.unwrap();
+        let message = vec![7u8; 100_000];  // 大于套接字缓冲区，读写都会遇到WouldBlock
+        let mut received = vec![0; message.len()];
+        let (mut reader, mut writer) = (&mut stream).split();
+        let write = async {
+            writer.write_all(&message).await.unwrap();
+            writer.close().await.unwrap();
+        };
+        let read = reader.read_exact(&mut received);
+        let ((), read) = futures::join!(write, read);
+        read.unwrap();
+        received == message
+    }).unwrap();
+    assert!(runtime.block_on(client).unwrap());
+    assert_eq!(100_000, runtime.block_on(server).unwrap());
+}
+
+#[test]
+fn accept_waits_for_blocking_client() {
+    let listener = AsyncTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"ping").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply
+    });
+    block_on(async {
+        let (mut stream, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer, stream.peer_addr().unwrap());
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(b"ping", &buf);
+        stream.write_all(b"pong").await.unwrap();
+    });
+    assert_eq!("pong", client.join().unwrap());
+}
+
+#[test]
+fn read_waits_until_data_arrives() {
+    let listener = AsyncTcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    block_on(async {
+        let mut client = AsyncTcpStream::connect(addr).await.unwrap();
+        let (_server, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 1];
+        let result = timeout(Duration::from_millis(50), client.read(&mut buf)).await;
+        assert!(result.is_err());  // 对端没有发送数据，读操作一直等待
+    });
+}
+
+#[test]
+fn connect_to_closed_port_fails() {
+    let addr = {
+        let listener = AsyncTcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    assert!(block_on(AsyncTcpStream::connect(addr)).is_err());
+}
@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use asynch::{Builder, Runtime, SpawnError, TaskState, TimerFuture};
+
+#[test]
+fn join_waits_for_all_tasks() {
+    let runtime = Builder::new().num_threads(4).build();
+    let count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..100 {
+        let count = count.clone();
+        runtime.spawn(async move {
+            TimerFuture::new(Duration::from_millis(5)).await;
+            count.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    runtime.join();
+    assert_eq!(100, count.load(Ordering::SeqCst));
+}
+
+#[test]
+fn tasks_run_on_multiple_workers() {
+    let runtime = Builder::new()
+        .num_threads(4)
+        .thread_name("asynch-worker".to_owned())
+        .build();
+    assert_eq!(4, runtime.num_threads());
+    let barrier = Arc::new(Barrier::new(4));
+    let names = Arc::new(Mutex::new(HashSet::new()));
+    for _ in 0..4 {
+        let barrier = barrier.clone();
+        let names = names.clone();
+        runtime.spawn(async move {
+            barrier.wait();  // 四个任务必须同时处于不同的工作线程中才能通过
+            let name = thread::current().name().map(str::to_owned);
+            names.lock().unwrap().insert(name);
+        }).unwrap();
+    }
+    runtime.join();
+    let names = names.lock().unwrap();
+    assert_eq!(1, names.len());
+    assert!(names.contains(&Some("asynch-worker".to_owned())));
+}
+
+#[test]
+fn spawn_returns_error_when_queue_is_full() {
+    let runtime = Builder::new().num_threads(1).queue_capacity(2).build();
+    let (started_tx, started_rx) = channel();
+    let (release_tx, release_rx) = channel::<()>();
+    runtime.spawn(async move {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();  // 阻塞唯一的工作线程
+    }).unwrap();
+    started_rx.recv().unwrap();
+    runtime.spawn(async {}).unwrap();
+    runtime.spawn(async {}).unwrap();
+    assert_eq!(Some(SpawnError::QueueFull), runtime.spawn(async {}).err());
+    release_tx.send(()).unwrap();
+    runtime.join();
+    runtime.spawn(async {}).unwrap();
+    runtime.join();
+}
+
+#[test]
+fn repeated_wakes_enqueue_task_once() {
+    let runtime = Builder::new().num_threads(1).queue_capacity(2).build();
+    let stop = Arc::new(AtomicUsize::new(0));
+    let polls = Arc::new(AtomicUsize::new(0));
+    let (started_tx, started_rx) = channel();
+    let (task_stop, task_polls) = (stop.clone(), polls.clone());
+    runtime.spawn(futures::future::poll_fn(move |cx| {
+        if task_polls.fetch_add(1, Ordering::SeqCst) == 0 {
+            started_tx.send(()).unwrap();
+        }
+        if task_stop.load(Ordering::SeqCst) > 0 {
+            return std::task::Poll::Ready(());
+        }
+        for _ in 0..10 {
+            cx.waker().wake_by_ref();  // 执行期间反复唤醒自身
+        }
+        std::task::Poll::Pending
+    })).unwrap();
+    started_rx.recv().unwrap();
+    // 反复唤醒的任务最多占用就绪队列中的一个位置
+    for _ in 0..100 {
+        let handle = runtime.spawn(async {}).unwrap();
+        runtime.block_on(handle).unwrap();
+    }
+    stop.store(1, Ordering::SeqCst);
+    runtime.join();
+    assert!(polls.load(Ordering::SeqCst) > 1);
+}
+
+#[test]
+fn spawner_fails_after_runtime_is_dropped() {
+    let runtime = Runtime::new();
+    let spawner = runtime.spawner();
+    spawner.spawn(async {}).unwrap();
+    drop(runtime);
+    assert_eq!(Some(SpawnError::Shutdown), spawner.spawn(async {}).err());
+}
+
+#[test]
+fn panicking_task_does_not_kill_worker() {
+    let runtime = Builder::new().num_threads(1).build();
+    runtime.spawn(async { panic!("task panicked") }).unwrap();
+    let (tx, rx) = channel();
+    runtime.spawn(async move { tx.send(42).unwrap() }).unwrap();
+    assert_eq!(42, rx.recv_timeout(Duration::from_secs(5)).unwrap());
+    runtime.join();
+}
+
+#[test]
+fn block_on_returns_future_output() {
+    assert_eq!(42, asynch::block_on(async { 40 + 2 }));
+    let value = asynch::block_on(async {
+        TimerFuture::new(Duration::from_millis(10)).await;
+        "slept"
+    });
+    assert_eq!("slept", value);
+}
+
+#[test]
+fn join_handle_returns_task_result() {
+    let runtime = Builder::new().num_threads(2).build();
+    let handle = runtime.spawn(async { 6 * 7 }).unwrap();
+    assert_eq!(42, runtime.block_on(handle).unwrap());
+}
+
+#[test]
+fn tasks_can_await_each_other() {
+    let runtime = Builder::new().num_threads(2).build();
+    let spawner = runtime.spawner();
+    let outer = runtime.spawn(async move {
+        let inner = spawner.spawn(async {
+            TimerFuture::new(Duration::from_millis(10)).await;
+            String::from("inner")
+        }).unwrap();
+        format!("{} done", inner.await.unwrap())
+    }).unwrap();
+    assert_eq!("inner done", runtime.block_on(outer).unwrap());
+}
+
+#[test]
+fn join_handle_reports_panics() {
+    let runtime = Builder::new().num_threads(1).build();
+    let handle = runtime.spawn(async {
+        if true {
+            panic!("join handle panic");
+        }
+    }).unwrap();
+    let err = runtime.block_on(handle).unwrap_err();
+    assert!(err.is_panic());
+    assert_eq!("task panicked: join handle panic", err.to_string());
+}
+
+#[test]
+fn join_handle_is_cancelled_when_runtime_drops() {
+    let runtime = Builder::new().num_threads(1).build();
+    let handle = runtime.spawn(TimerFuture::new(Duration::from_secs(60))).unwrap();
+    assert!(!handle.is_finished());
+    drop(runtime);
+    assert!(asynch::block_on(handle).unwrap_err().is_cancelled());
+}
+
+#[test]
+fn dump_lists_live_tasks_with_names() {
+    let runtime = Builder::new().num_threads(2).build();
+    let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    let waiting = runtime.spawn_named("waiter", async move {
+        TimerFuture::new(Duration::from_millis(1)).await;
+        rx.await.unwrap();
+    }).unwrap();
+    runtime.spawn(TimerFuture::new(Duration::from_secs(60))).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    let dump = runtime.dump();
+    assert_eq!(2, dump.len());
+    assert_eq!(Some("waiter"), dump[0].name.as_deref());
+    assert_eq!(TaskState::Idle, dump[0].state);
+    assert_eq!(2, dump[0].poll_count);
+    assert_eq!(None, dump[1].name);
+    assert_eq!(1, dump[1].poll_count);
+    assert!(dump[0].to_string().starts_with("task #0 'waiter': idle, 2 polls in"));
+    tx.send(()).unwrap();
+    runtime.block_on(waiting).unwrap();
+    assert_eq!(1, runtime.dump().len());
+}
+
+#[test]
+fn slow_polls_are_counted() {
+    let runtime = Builder::new()
+        .num_threads(1)
+        .slow_poll_threshold(Duration::from_millis(10))
+        .build();
+    let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    runtime.spawn_named("hog", async move {
+        thread::sleep(Duration::from_millis(30));  // 阻塞工作线程，触发慢推进警告
+        rx.await.unwrap();
+    }).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    let dump = runtime.dump();
+    assert_eq!(1, dump[0].slow_polls);
+    assert!(dump[0].poll_time >= Duration::from_millis(30));
+    tx.send(()).unwrap();
+    runtime.join();
+}
@@ -0,0 +1,176 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::stream::StreamExt;
+
+use asynch::sync::{mpsc, oneshot, Mutex, RecvError, Semaphore, SendError, TryRecvError, TrySendError};
+use asynch::{block_on, sleep, timeout, Builder};
+
+#[test]
+fn bounded_channel_applies_backpressure() {
+    let runtime = Builder::new().num_threads(2).build();
+    let (tx, mut rx) = mpsc::channel(2);
+    tx.try_send(1).unwrap();
+    tx.try_send(2).unwrap();
+    assert_eq!(Err(TrySendError::Full(3)), tx.try_send(3));
+    let sent = Arc::new(AtomicUsize::new(0));
+    let producer = {
+        let sent = sent.clone();
+        runtime.spawn(async move {
+            for i in 3..=10 {
+                tx.send(i).await.unwrap();
+                sent.fetch_add(1, Ordering::SeqCst);
+            }
+        }).unwrap()
+    };
+    runtime.block_on(sleep(Duration::from_millis(20)));
+    assert_eq!(0, sent.load(Ordering::SeqCst));  // 通道已满，发送端一直等待
+    let received: Vec<i32> = runtime.block_on(async {
+        let mut received = Vec::new();
+        while let Some(value) = rx.recv().await {
+            received.push(value);
+        }
+        received
+    });
+    assert_eq!((1..=10).collect::<Vec<_>>(), received);
+    runtime.block_on(producer).unwrap();
+}
+
+#[test]
+fn unbounded_channel_with_many_producers() {
+    let runtime = Builder::new().num_threads(4).build();
+    let (tx, rx) = mpsc::unbounded_channel();
+    for producer in 0..4 {
+        let tx = tx.clone();
+        runtime.spawn(async move {
+            for i in 0..100 {
+                tx.send(producer * 100 + i).unwrap();
+            }
+        }).unwrap();
+    }
+    drop(tx);
+    let mut received: Vec<i32> = runtime.block_on(rx.collect());
+    received.sort_unstable();
+    assert_eq!((0..400).collect::<Vec<_>>(), received);
+}
+
+#[test]
+fn channel_reports_closed_ends() {
+    let (tx, mut rx) = mpsc::channel::<i32>(1);
+    assert_eq!(Err(TryRecvError::Empty), rx.try_recv());
+    block_on(tx.send(0)).unwrap();
+    rx.close();
+    assert!(tx.is_closed());
+    assert_eq!(Err(SendError(1)), block_on(tx.send(1)));
+    assert_eq!(Ok(0), rx.try_recv());  // 关闭前已在通道中的消息仍可取出
+    assert_eq!(Err(TryRecvError::Closed), rx.try_recv());  // 发送端仍存活，但接收端已关闭
+    assert_eq!(None, block_on(rx.recv()));
+    drop(tx);
+    assert_eq!(Err(TryRecvError::Closed), rx.try_recv());
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    drop(rx);
+    assert_eq!(Err(SendError("lost")), tx.send("lost"));
+}
+
+#[test]
+fn oneshot_delivers_value_or_error() {
+    let runtime = Builder::new().num_threads(2).build();
+    let (tx, rx) = oneshot::channel();
+    runtime.spawn(async move {
+        sleep(Duration::from_millis(10)).await;
+        tx.send("ready").unwrap();
+    }).unwrap();
+    assert_eq!(Ok("ready"), runtime.block_on(rx));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    drop(tx);
+    assert_eq!(Err(RecvError), block_on(rx));
+
+    let (tx, rx) = oneshot::channel();
+    drop(rx);
+    assert!(tx.is_closed());
+    assert_eq!(Err(5), tx.send(5));
+}
+
+#[test]
+fn async_mutex_can_be_held_across_await() {
+    let runtime = Builder::new().num_threads(2).build();
+    let counter = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..50)
+        .map(|_| {
+            let counter = counter.clone();
+            runtime.spawn(async move {
+                let mut value = counter.lock().await;
+                let current = *value;
+                sleep(Duration::from_millis(1)).await;  // 持有锁期间让出，其他任务不能修改
+                *value = current + 1;
+            }).unwrap()
+        })
+        .collect();
+    for handle in handles {
+        runtime.block_on(handle).unwrap();
+    }
+    assert_eq!(50, *block_on(counter.lock()));
+}
+
+#[test]
+fn waiting_for_mutex_does_not_block_worker() {
+    let runtime = Builder::new().num_threads(1).build();
+    let mutex = Arc::new(Mutex::new(()));
+    let guard = mutex.try_lock().unwrap();
+    assert!(mutex.try_lock().is_none());
+    let waiter = {
+        let mutex = mutex.clone();
+        runtime.spawn(async move {
+            let _guard = mutex.lock().await;
+        }).unwrap()
+    };
+    // 唯一的工作线程没有被等待锁的任务占住，仍能执行其他任务
+    let other = runtime.spawn(async { 7 }).unwrap();
+    assert_eq!(7, runtime.block_on(other).unwrap());
+    drop(guard);
+    runtime.block_on(waiter).unwrap();
+}
+
+#[test]
+fn semaphore_limits_concurrency() {
+    let runtime = Builder::new().num_threads(4).build();
+    let semaphore = Arc::new(Semaphore::new(2));
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..10)
+        .map(|_| {
+            let semaphore = semaphore.clone();
+            let running = running.clone();
+            let max_running = max_running.clone();
+            runtime.spawn(async move {
+                let _permit = semaphore.acquire().await;
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            }).unwrap()
+        })
+        .collect();
+    for handle in handles {
+        runtime.block_on(handle).unwrap();
+    }
+    assert_eq!(2, max_running.load(Ordering::SeqCst));
+    assert_eq!(2, semaphore.available_permits());
+}
+
+#[test]
+fn cancelled_acquire_passes_permit_on() {
+    let semaphore = Semaphore::new(1);
+    let permit = semaphore.try_acquire().unwrap();
+    block_on(async {
+        // 排队等待的acquire超时被丢弃后，许可应交给后面的等待者
+        assert!(timeout(Duration::from_millis(10), semaphore.acquire()).await.is_err());
+        drop(permit);
+        let _permit = timeout(Duration::from_secs(5), semaphore.acquire()).await.unwrap();
+        assert_eq!(0, semaphore.available_permits());
+    });
+    assert_eq!(1, semaphore.available_permits());
+}
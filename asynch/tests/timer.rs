@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use asynch::{block_on, interval, pending_timers, sleep, sleep_until, timeout, Builder, Elapsed, TimerFuture};
+
+#[test]
+fn sleep_waits_for_duration() {
+    let start = Instant::now();
+    block_on(sleep(Duration::from_millis(30)));
+    assert!(start.elapsed() >= Duration::from_millis(30));
+    let deadline = Instant::now() + Duration::from_millis(20);
+    block_on(sleep_until(deadline));
+    assert!(Instant::now() >= deadline);
+    block_on(sleep_until(Instant::now() - Duration::from_millis(1)));  // 已过期的时刻立即完成
+}
+
+#[test]
+fn timers_fire_in_deadline_order() {
+    let runtime = Builder::new().num_threads(4).build();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    for &millis in &[50u64, 10, 30, 20, 40] {
+        let order = order.clone();
+        runtime.spawn(async move {
+            sleep(Duration::from_millis(millis)).await;
+            order.lock().unwrap().push(millis);
+        }).unwrap();
+    }
+    runtime.join();
+    assert_eq!(vec![10, 20, 30, 40, 50], *order.lock().unwrap());
+}
+
+#[test]
+fn many_timers_share_one_driver() {
+    let runtime = Builder::new().num_threads(2).build();
+    let fired = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+    for i in 0..10_000u64 {
+        let fired = fired.clone();
+        runtime.spawn(async move {
+            sleep(Duration::from_millis(10 + i % 50)).await;
+            fired.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    runtime.join();
+    assert_eq!(10_000, fired.load(Ordering::SeqCst));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn dropped_timers_are_removed_from_driver() {
+    for _ in 0..100_000 {
+        drop(TimerFuture::new(Duration::from_secs(3600)));
+    }
+    // 并行的其他测试最多同时登记一万个定时器
+    assert!(pending_timers() < 50_000);
+    let kept: Vec<_> = (0..1000).map(|_| TimerFuture::new(Duration::from_secs(3600))).collect();
+    assert!(pending_timers() >= 1000);
+    drop(kept);
+}
+
+#[test]
+fn interval_ticks_periodically() {
+    let start = Instant::now();
+    let ticks = block_on(async {
+        let mut interval = interval(Duration::from_millis(10));
+        let mut ticks = Vec::new();
+        for _ in 0..3 {
+            ticks.push(interval.tick().await);
+        }
+        ticks
+    });
+    assert!(start.elapsed() >= Duration::from_millis(30));
+    for pair in ticks.windows(2) {
+        assert!(pair[1] - pair[0] >= Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn timeout_returns_output_or_elapsed() {
+    let fast = block_on(timeout(Duration::from_secs(5), async { 7 }));
+    assert_eq!(Ok(7), fast);
+    let slow = block_on(timeout(Duration::from_millis(10), sleep(Duration::from_secs(5))));
+    assert_eq!(Err(Elapsed), slow);
+}
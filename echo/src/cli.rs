@@ -0,0 +1,195 @@
+use std::error::Error;
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::protocol::Mode;
+use crate::server::{Builder, Overflow};
+use crate::transport::Endpoint;
+
+/// 命令行用法
+pub const USAGE: &str = "\
+Usage: echo [OPTIONS]
+
+Options:
+  -t, --transport <tcp|udp|unix>  transport to serve on [default: tcp]
+  -b, --bind <ADDR>               address or socket path to bind
+                                  [default: 127.0.0.1:8080, or /tmp/echo.sock for unix]
+      --line                      use the line-based command protocol
+      --workers <N>               number of worker threads [default: CPU count]
+      --max-connections <N>       maximum number of connections [default: workers]
+      --reject                    reject connections over the limit instead of queueing them
+      --idle-timeout <SECS>       close connections idle for this many seconds
+      --drain-timeout <SECS>      seconds to wait for connections on shutdown [default: 5]
+      --log <PATH>                append connection events to PATH as JSON lines
+  -h, --help                      print this help";
+
+/// Unix域socket的默认路径
+const DEFAULT_UNIX_PATH: &str = "/tmp/echo.sock";
+/// TCP和UDP的默认地址
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+/// 解析命令行参数失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    Help,  // 请求打印帮助
+    Invalid(String),  // 参数无效，携带错误信息
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CliError::Help => f.write_str(USAGE),
+            CliError::Invalid(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+/// 传输方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transport {
+    Tcp,
+    Udp,
+    Unix,
+}
+
+/// 命令行参数解析出的服务器配置
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub endpoint: Endpoint,  // 监听的地址及传输方式
+    pub mode: Mode,  // 连接的处理模式
+    pub workers: Option<usize>,  // 工作线程数
+    pub max_connections: Option<usize>,  // 最大连接数
+    pub overflow: Overflow,  // 连接数达到上限时的处理方式
+    pub idle_timeout: Option<Duration>,  // 空闲超时时间
+    pub drain_timeout: Option<Duration>,  // 关闭时等待已有连接结束的时间
+    pub log_file: Option<PathBuf>,  // 事件日志文件
+}
+
+impl Options {
+    /// 解析命令行参数，不包括程序名
+    pub fn parse<I, S>(args: I) -> Result<Options, CliError>
+        where I: IntoIterator<Item = S>,
+              S: Into<String>
+    {
+        let mut transport = Transport::Tcp;
+        let mut bind = None;
+        let mut mode = Mode::Raw;
+        let mut workers = None;
+        let mut max_connections = None;
+        let mut overflow = Overflow::Queue;
+        let mut idle_timeout = None;
+        let mut drain_timeout = None;
+        let mut log_file = None;
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            // 同时支持`--name value`和`--name=value`
+            let (name, inline) = match arg.find('=') {
+                Some(index) if arg.starts_with("--") => (arg[..index].to_string(), Some(arg[index + 1..].to_string())),
+                _ => (arg, None),
+            };
+            let mut value = || inline.clone().or_else(|| args.next())
+                .ok_or_else(|| CliError::Invalid(format!("missing value for '{}'", name)));
+            match name.as_str() {
+                "-h" | "--help" => {
+                    flag(&name, &inline)?;
+                    return Err(CliError::Help);
+                }
+                "-t" | "--transport" => {
+                    transport = match value()?.as_str() {
+                        "tcp" => Transport::Tcp,
+                        "udp" => Transport::Udp,
+                        "unix" => Transport::Unix,
+                        other => return Err(CliError::Invalid(format!("unknown transport '{}'", other))),
+                    }
+                }
+                "-b" | "--bind" => bind = Some(value()?),
+                "--line" => {
+                    flag(&name, &inline)?;
+                    mode = Mode::Line;
+                }
+                "--workers" => workers = Some(positive(&name, &value()?)?),
+                "--max-connections" => max_connections = Some(positive(&name, &value()?)?),
+                "--reject" => {
+                    flag(&name, &inline)?;
+                    overflow = Overflow::Reject;
+                }
+                "--idle-timeout" => idle_timeout = Some(seconds(&name, &value()?)?),
+                "--drain-timeout" => drain_timeout = Some(seconds(&name, &value()?)?),
+                "--log" => log_file = Some(PathBuf::from(value()?)),
+                _ => return Err(CliError::Invalid(format!("unknown option '{}'", name))),
+            }
+        }
+        let endpoint = match transport {
+            Transport::Tcp => Endpoint::Tcp(resolve(bind.as_deref().unwrap_or(DEFAULT_ADDR))?),
+            Transport::Udp => Endpoint::Udp(resolve(bind.as_deref().unwrap_or(DEFAULT_ADDR))?),
+            Transport::Unix => Endpoint::Unix(PathBuf::from(bind.as_deref().unwrap_or(DEFAULT_UNIX_PATH))),
+        };
+        Ok(Options {
+            endpoint,
+            mode,
+            workers,
+            max_connections,
+            overflow,
+            idle_timeout,
+            drain_timeout,
+            log_file,
+        })
+    }
+
+    /// 按配置创建服务器构造器，地址由`endpoint`给出
+    pub fn builder(&self) -> Builder {
+        let mut builder = Builder::new()
+            .mode(self.mode)
+            .overflow(self.overflow);
+        if let Some(workers) = self.workers {
+            builder = builder.workers(workers);
+        }
+        if let Some(max) = self.max_connections {
+            builder = builder.max_connections(max);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder = builder.idle_timeout(timeout);
+        }
+        if let Some(timeout) = self.drain_timeout {
+            builder = builder.drain_timeout(timeout);
+        }
+        if let Some(ref path) = self.log_file {
+            builder = builder.log_file(path.clone());
+        }
+        builder
+    }
+}
+
+/// 开关选项不接受`--name=value`形式的值
+fn flag(name: &str, inline: &Option<String>) -> Result<(), CliError> {
+    match *inline {
+        Some(_) => Err(CliError::Invalid(format!("'{}' does not take a value", name))),
+        None => Ok(()),
+    }
+}
+
+fn positive(name: &str, value: &str) -> Result<usize, CliError> {
+    match usize::from_str(value) {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(CliError::Invalid(format!("'{}' expects a positive integer, got '{}'", name, value))),
+    }
+}
+
+fn seconds(name: &str, value: &str) -> Result<Duration, CliError> {
+    // 超出Duration范围或不足1纳秒的值同样视为无效
+    match f64::from_str(value).ok().and_then(|secs| Duration::try_from_secs_f64(secs).ok()) {
+        Some(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(CliError::Invalid(format!("'{}' expects a positive number of seconds, got '{}'", name, value))),
+    }
+}
+
+fn resolve(addr: &str) -> Result<SocketAddr, CliError> {
+    addr.to_socket_addrs().ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| CliError::Invalid(format!("invalid address '{}'", addr)))
+}
@@ -0,0 +1,14 @@
+mod cli;
+mod limit;
+mod metrics;
+mod protocol;
+mod server;
+mod shutdown;
+mod transport;
+
+pub use cli::{CliError, Options, USAGE};
+pub use metrics::{ConnectionRecord, Metrics};
+pub use protocol::Mode;
+pub use server::{Builder, Overflow, Server};
+pub use shutdown::{ShutdownHandle, Summary};
+pub use transport::Endpoint;
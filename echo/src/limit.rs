@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// 关闭连接的回调，服务器关闭期限到达时调用以打断连接上阻塞的读取
+pub(crate) type Closer = Box<dyn Fn() + Send>;
+
+struct LimitState {
+    count: usize,  // 当前连接数
+    next_id: u64,  // 下一个连接的编号
+    closers: HashMap<u64, Closer>,  // 存活连接的关闭回调
+}
+
+/// 限制同时存在的连接数，包括正在处理和在线程池中排队的连接
+pub(crate) struct ConnectionLimit {
+    max: usize,  // 允许的最大连接数
+    state: Mutex<LimitState>,
+    released: Condvar,  // 有连接关闭时通知等待的accept线程和关闭服务器的线程
+}
+
+impl ConnectionLimit {
+    pub(crate) fn new(max: usize) -> Arc<ConnectionLimit> {
+        Arc::new(ConnectionLimit {
+            max,
+            state: Mutex::new(LimitState {
+                count: 0,
+                next_id: 0,
+                closers: HashMap::new(),
+            }),
+            released: Condvar::new(),
+        })
+    }
+
+    /// 阻塞直到有空闲的连接名额，不占用名额；超时返回false
+    pub(crate) fn wait_for_slot(&self, timeout: Duration) -> bool {
+        let state = self.state.lock()
+            .expect("unable to lock connection limit");
+        let (state, _) = self.released.wait_timeout_while(state, timeout, |state| state.count >= self.max)
+            .expect("unable to wait for connection slot");
+        state.count < self.max
+    }
+
+    /// 尝试获取连接名额并登记连接的关闭回调，已达上限时返回None
+    pub(crate) fn try_acquire(self: &Arc<Self>, closer: Closer) -> Option<ConnectionPermit> {
+        let mut state = self.state.lock()
+            .expect("unable to lock connection limit");
+        if state.count >= self.max {
+            return None;
+        }
+        state.count += 1;
+        let id = state.next_id;
+        state.next_id += 1;
+        state.closers.insert(id, closer);
+        Some(ConnectionPermit {
+            limit: self.clone(),
+            id,
+        })
+    }
+
+    /// 当前连接数
+    pub(crate) fn count(&self) -> usize {
+        self.state.lock()
+            .expect("unable to lock connection limit")
+            .count
+    }
+
+    /// 阻塞直到所有连接结束或到达期限，返回连接是否已全部结束
+    pub(crate) fn wait_idle(&self, deadline: Instant) -> bool {
+        let mut state = self.state.lock()
+            .expect("unable to lock connection limit");
+        while state.count > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = self.released.wait_timeout(state, deadline - now)
+                .expect("unable to wait for connections").0;
+        }
+        true
+    }
+
+    /// 调用所有存活连接的关闭回调，返回关闭的连接数
+    pub(crate) fn close_all(&self) -> usize {
+        let state = self.state.lock()
+            .expect("unable to lock connection limit");
+        for closer in state.closers.values() {
+            closer();
+        }
+        state.closers.len()
+    }
+}
+
+/// 连接名额，随连接处理任务一起提交，任务结束或被丢弃时归还
+pub(crate) struct ConnectionPermit {
+    limit: Arc<ConnectionLimit>,
+    id: u64,  // 连接编号，用于移除关闭回调
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let closer = {
+            let mut state = self.limit.state.lock()
+                .expect("unable to lock connection limit");
+            state.count -= 1;
+            self.limit.released.notify_all();
+            state.closers.remove(&self.id)
+        };
+        drop(closer);  // 在锁外释放回调持有的连接
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::shutdown::Summary;
+
+/// 服务器整体统计信息的快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct Metrics {
+    pub accepted: u64,  // 接受的连接数
+    pub rejected: u64,  // 因连接数达到上限被拒绝的连接数
+    pub active: u64,  // 尚未结束的连接数
+    pub closed: u64,  // 已结束的连接数
+    pub bytes_in: u64,  // 所有连接收到的字节数
+    pub bytes_out: u64,  // 所有连接发送的字节数
+}
+
+/// 服务器整体的统计信息，由所有连接共同更新
+#[derive(Debug, Default)]
+pub(crate) struct ServerMetrics {
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    closed: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl ServerMetrics {
+    pub(crate) fn connection_accepted(&self) {
+        self.accepted.fetch_add(1, Ordering::SeqCst);
+    }
+    pub(crate) fn connection_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::SeqCst);
+    }
+    pub(crate) fn connection_closed(&self) {
+        self.closed.fetch_add(1, Ordering::SeqCst);
+    }
+    pub(crate) fn add_bytes_in(&self, n: usize) {
+        self.bytes_in.fetch_add(n as u64, Ordering::SeqCst);
+    }
+    pub(crate) fn add_bytes_out(&self, n: usize) {
+        self.bytes_out.fetch_add(n as u64, Ordering::SeqCst);
+    }
+    pub(crate) fn snapshot(&self) -> Metrics {
+        let closed = self.closed.load(Ordering::SeqCst);
+        let accepted = self.accepted.load(Ordering::SeqCst);
+        Metrics {
+            accepted,
+            rejected: self.rejected.load(Ordering::SeqCst),
+            active: accepted.saturating_sub(closed),
+            closed,
+            bytes_in: self.bytes_in.load(Ordering::SeqCst),
+            bytes_out: self.bytes_out.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// 单个连接结束时的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionRecord {
+    pub transport: &'static str,  // 传输方式，tcp、udp或unix
+    pub peer: String,  // 对端地址
+    pub bytes_in: u64,  // 收到的字节数
+    pub bytes_out: u64,  // 发送的字节数
+    pub commands: u64,  // 行模式下处理的命令数
+    pub duration_ms: u64,  // 连接持续的时间，单位为毫秒
+    pub close: String,  // 关闭原因，如eof、idle timeout、quit、shutdown或error: ...
+}
+
+/// 写入日志的事件
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum Event<'a> {
+    Connection(&'a ConnectionRecord),  // 连接结束
+    Rejected {  // 连接因数量达到上限被拒绝
+        transport: &'static str,
+        peer: &'a str,
+    },
+    Shutdown {  // 服务器关闭
+        summary: &'a Summary,
+        metrics: Metrics,
+    },
+}
+
+/// 日志中的一行，事件的字段与时间戳位于同一层
+#[derive(Serialize)]
+struct LogLine<'a> {
+    timestamp: f64,  // UNIX时间戳，单位为秒
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+/// 以JSON lines格式追加写入的事件日志
+pub(crate) struct EventLog {
+    file: Mutex<File>,
+}
+
+impl EventLog {
+    pub(crate) fn open(path: &Path) -> io::Result<EventLog> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(EventLog { file: Mutex::new(file) })
+    }
+
+    /// 写入一个事件，每个事件一行；写入失败只打印错误，不影响连接的处理
+    pub(crate) fn write(&self, event: &Event) {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let mut line = match serde_json::to_string(&LogLine { timestamp, event }) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("failed to serialize log event: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+        let mut file = self.file.lock()
+            .expect("unable to lock event log");
+        if let Err(e) = file.write_all(line.as_bytes()) {  // 整行一次写入，避免多个连接的日志交错
+            eprintln!("failed to write log event: {}", e);
+        }
+    }
+}
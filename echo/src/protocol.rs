@@ -0,0 +1,250 @@
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Write};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::limit::ConnectionLimit;
+use crate::metrics::ServerMetrics;
+
+/// 单行允许的最大长度，超过后回复错误并断开连接，避免缓存无限增长
+const MAX_LINE_LEN: usize = 8 * 1024;
+
+/// 连接的处理模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Raw,  // 原样回显收到的字节
+    Line,  // 按行解析命令并回复
+}
+
+/// 连接关闭的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Close {
+    Eof,  // 客户端关闭了连接
+    Idle,  // 空闲超时
+    Quit,  // 客户端发送了QUIT命令
+    LineTooLong,  // 行模式下单行超过长度限制
+    Shutdown,  // 服务器关闭时被强制断开
+}
+
+impl fmt::Display for Close {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Close::Eof => write!(f, "eof"),
+            Close::Idle => write!(f, "idle timeout"),
+            Close::Quit => write!(f, "quit"),
+            Close::LineTooLong => write!(f, "line too long"),
+            Close::Shutdown => write!(f, "shutdown"),
+        }
+    }
+}
+
+/// 行模式支持的命令
+#[derive(Debug, PartialEq, Eq)]
+enum Command<'a> {
+    Echo(&'a str),  // 原样返回参数
+    Upper(&'a str),  // 返回大写的参数
+    Time,  // 返回服务器当前的UNIX时间戳，单位为秒，精确到毫秒
+    Stats,  // 返回连接和服务器整体的统计信息
+    Quit,  // 回复后断开连接
+    Unknown(&'a str),  // 无法识别的命令
+}
+
+impl<'a> Command<'a> {
+    /// 解析一行命令，命令名不区分大小写，与参数以第一个空格分隔
+    fn parse(line: &'a str) -> Command<'a> {
+        let (name, arg) = match line.find(' ') {
+            Some(index) => (&line[..index], &line[index + 1..]),
+            None => (line, ""),
+        };
+        match name.to_ascii_uppercase().as_str() {
+            "ECHO" => Command::Echo(arg),
+            "UPPER" => Command::Upper(arg),
+            "TIME" => Command::Time,
+            "STATS" => Command::Stats,
+            "QUIT" => Command::Quit,
+            _ => Command::Unknown(name),
+        }
+    }
+}
+
+/// 单个连接的状态和统计信息
+pub(crate) struct Session {
+    limit: Arc<ConnectionLimit>,  // 用于在处理STATS时获取服务器当前的连接数
+    metrics: Arc<ServerMetrics>,  // 服务器整体的统计信息，收发数据时同步更新
+    pub(crate) commands: usize,  // 已处理的命令数
+    pub(crate) bytes_in: usize,  // 收到的字节数
+    pub(crate) bytes_out: usize,  // 发送的字节数
+}
+
+impl Session {
+    pub(crate) fn new(limit: Arc<ConnectionLimit>, metrics: Arc<ServerMetrics>) -> Session {
+        Session {
+            limit,
+            metrics,
+            commands: 0,
+            bytes_in: 0,
+            bytes_out: 0,
+        }
+    }
+
+    /// 读取数据，空闲超时返回None
+    fn read<S: Read>(&mut self, stream: &mut S, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        loop {
+            match stream.read(buf) {
+                Ok(n) => {
+                    self.bytes_in += n;
+                    self.metrics.add_bytes_in(n);
+                    return Ok(Some(n));
+                }
+                // 设置了读超时的socket超时后返回WouldBlock或TimedOut，视平台而定
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    return Ok(None);
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn write<S: Write>(&mut self, stream: &mut S, data: &[u8]) -> io::Result<()> {
+        stream.write_all(data)?;
+        self.bytes_out += data.len();
+        self.metrics.add_bytes_out(data.len());
+        Ok(())
+    }
+
+    /// 写入一行回复
+    fn reply<S: Write>(&mut self, stream: &mut S, status: &str, text: &str) -> io::Result<()> {
+        let line = if text.is_empty() {
+            format!("{}\r\n", status)
+        } else {
+            format!("{} {}\r\n", status, text)
+        };
+        self.write(stream, line.as_bytes())
+    }
+
+    /// 执行一条命令，返回Some时关闭连接
+    fn execute<S: Write>(&mut self, stream: &mut S, line: &str) -> io::Result<Option<Close>> {
+        self.commands += 1;
+        match Command::parse(line) {
+            Command::Echo(text) => self.reply(stream, "OK", text)?,
+            Command::Upper(text) => self.reply(stream, "OK", &text.to_uppercase())?,
+            Command::Time => self.reply(stream, "OK", &unix_time())?,
+            Command::Stats => {
+                let total = self.metrics.snapshot();
+                let stats = format!("connections={} commands={} bytes_in={} bytes_out={} \
+                                     accepted={} rejected={} closed={} total_bytes_in={} total_bytes_out={}",
+                                    self.limit.count(), self.commands, self.bytes_in, self.bytes_out,
+                                    total.accepted, total.rejected, total.closed,
+                                    total.bytes_in, total.bytes_out);
+                self.reply(stream, "OK", &stats)?
+            }
+            Command::Quit => {
+                self.reply(stream, "OK", "bye")?;
+                return Ok(Some(Close::Quit));
+            }
+            Command::Unknown(name) => {
+                self.reply(stream, "ERR", &format!("unknown command '{}'", name))?
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// 缓存不完整的行，直到收到换行符
+#[derive(Debug, Default)]
+struct LineBuffer {
+    buf: Vec<u8>,
+}
+
+impl LineBuffer {
+    fn extend(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// 取出下一个完整的行，去掉行尾的`\n`或`\r\n`
+    fn next_line(&mut self) -> Option<Vec<u8>> {
+        let end = self.buf.iter().position(|&b| b == b'\n')?;
+        let mut line: Vec<u8> = self.buf.drain(..=end).collect();
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(line)
+    }
+
+    /// 取出剩余的不完整行，为空时返回None
+    fn take_rest(&mut self) -> Option<Vec<u8>> {
+        if self.buf.is_empty() {
+            return None;
+        }
+        let mut line = std::mem::take(&mut self.buf);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(line)
+    }
+
+    fn len(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+fn unix_time() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}.{:03}", now.as_secs(), now.subsec_millis())
+}
+
+/// 原样回显收到的数据
+pub(crate) fn serve_raw<S: Read + Write>(stream: &mut S, session: &mut Session) -> io::Result<Close> {
+    let mut buf = [0; 1024];  // 创建缓存
+    loop {
+        let bytes_read = match session.read(stream, &mut buf)? {  // 读取数据
+            Some(n) => n,
+            None => return Ok(Close::Idle),
+        };
+        if bytes_read == 0 { return Ok(Close::Eof); }   // EOF，断开连接
+        session.write(stream, &buf[..bytes_read])?;     // echo
+    }
+}
+
+/// 执行一行命令，空行被忽略，命令要求关闭连接时返回关闭原因
+fn serve_line<S: Write>(stream: &mut S, session: &mut Session, line: Vec<u8>) -> io::Result<Option<Close>> {
+    match String::from_utf8(line) {
+        Ok(ref line) if line.trim().is_empty() => Ok(None),
+        Ok(line) => session.execute(stream, &line),
+        Err(_) => session.reply(stream, "ERR", "invalid utf-8").map(|()| None),
+    }
+}
+
+/// 以行模式处理连接，每条命令回复一行`OK ...`或`ERR ...`，空行被忽略
+pub(crate) fn serve_lines<S: Read + Write>(stream: &mut S, session: &mut Session) -> io::Result<Close> {
+    let mut lines = LineBuffer::default();
+    let mut buf = [0; 1024];
+    loop {
+        let bytes_read = match session.read(stream, &mut buf)? {
+            Some(n) => n,
+            None => return Ok(Close::Idle),
+        };
+        if bytes_read == 0 {  // EOF，没有换行结尾的最后一行同样作为命令执行（如单个数据报）
+            if let Some(line) = lines.take_rest() {
+                if let Some(close) = serve_line(stream, session, line)? {
+                    return Ok(close);
+                }
+            }
+            return Ok(Close::Eof);
+        }
+        lines.extend(&buf[..bytes_read]);
+        while let Some(line) = lines.next_line() {
+            if let Some(close) = serve_line(stream, session, line)? {
+                return Ok(close);
+            }
+        }
+        if lines.len() > MAX_LINE_LEN {
+            session.reply(stream, "ERR", "line too long")?;
+            return Ok(Close::LineTooLong);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::io::{self, ErrorKind, Write};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use thread_pool::ThreadPool;
+
+use crate::limit::{ConnectionLimit, ConnectionPermit};
+use crate::metrics::{ConnectionRecord, Event, EventLog, Metrics, ServerMetrics};
+use crate::protocol::{self, Close, Mode, Session};
+use crate::shutdown::{self, ShutdownHandle, Summary};
+use crate::transport::{self, Connection, Endpoint, Listener};
+
+/// 连接数达到上限时对新连接的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    #[default]
+    Queue,  // 暂停accept，新连接在系统的连接队列中等待
+    Reject,  // 回复错误信息后立即关闭新连接
+}
+
+/// 拒绝连接时回复的信息
+const BUSY_MESSAGE: &[u8] = b"ERR server busy\r\n";
+
+/// accept线程检查关闭请求的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 所有连接的处理任务共享的配置和统计信息
+struct Context {
+    transport: &'static str,  // 传输方式的名称
+    mode: Mode,  // 连接的处理模式
+    idle_timeout: Option<Duration>,  // 连接空闲超时时间，None表示不超时
+    limit: Arc<ConnectionLimit>,  // 同时存在的连接数限制
+    metrics: Arc<ServerMetrics>,  // 服务器整体的统计信息
+    log: Option<EventLog>,  // 事件日志，None表示不记录
+}
+
+impl Context {
+    fn log(&self, event: &Event) {
+        if let Some(ref log) = self.log {
+            log.write(event);
+        }
+    }
+}
+
+/// echo服务器，在固定大小的线程池中处理TCP、UDP或Unix域socket上的连接
+pub struct Server {
+    listener: Box<dyn Listener>,
+    pool: ThreadPool,  // 处理连接的线程池
+    context: Arc<Context>,  // 连接的处理任务共享的配置和统计信息
+    overflow: Overflow,  // 连接数达到上限时的处理方式
+    drain_timeout: Duration,  // 关闭时等待已有连接结束的时间
+    shutdown: ShutdownHandle,  // 关闭请求
+}
+
+impl Server {
+    /// 以默认配置绑定TCP地址
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Server> {
+        Builder::new().bind(addr)
+    }
+
+    /// 实际绑定的TCP或UDP地址，绑定端口0时可用于获取分配的端口；Unix域socket返回错误
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self.listener.local_endpoint()? {
+            Endpoint::Tcp(addr) | Endpoint::Udp(addr) => Ok(addr),
+            Endpoint::Unix(_) => Err(io::Error::new(ErrorKind::InvalidInput,
+                                                    "unix socket has no socket address")),
+        }
+    }
+
+    /// 实际监听的地址及传输方式
+    pub fn local_endpoint(&self) -> io::Result<Endpoint> {
+        self.listener.local_endpoint()
+    }
+
+    /// 当前连接数，包括正在处理和排队等待处理的连接
+    pub fn connection_count(&self) -> usize {
+        self.context.limit.count()
+    }
+
+    /// 服务器整体的统计信息
+    pub fn metrics(&self) -> Metrics {
+        self.context.metrics.snapshot()
+    }
+
+    /// 获取关闭服务器的句柄
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// 循环接受连接并提交到线程池处理，直到通过`ShutdownHandle`请求关闭
+    ///
+    /// 关闭时停止接受新连接，等待已有连接在期限内结束，仍未结束的连接被强制断开，
+    /// 所有连接的处理任务结束后返回统计信息
+    pub fn run(&self) -> Summary {
+        let mut summary = Summary::default();
+        while !self.shutdown.is_shutdown() {
+            // 先等待名额再accept，多余的连接留在系统队列中
+            if self.overflow == Overflow::Queue && !self.context.limit.wait_for_slot(POLL_INTERVAL) {
+                continue;
+            }
+            match shutdown::wait_readable(&*self.listener, POLL_INTERVAL) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    eprintln!("failed: {}", e);
+                    continue;
+                }
+            }
+            let conn = match self.accept() {
+                Some(conn) => conn,
+                None => continue,
+            };
+            let closer = match conn.closer() {
+                Ok(closer) => closer,
+                Err(e) => {
+                    eprintln!("failed: {}", e);
+                    continue;
+                }
+            };
+            // 记录连接是否被服务器强制断开，以便区分客户端主动关闭
+            let forced = Arc::new(AtomicBool::new(false));
+            let forced_by_closer = forced.clone();
+            let closer = Box::new(move || {
+                forced_by_closer.store(true, Ordering::SeqCst);
+                closer();
+            });
+            // 只有accept线程占用名额，等到名额后不会被其他连接抢走
+            match self.context.limit.try_acquire(closer) {
+                Some(permit) => {
+                    summary.served += 1;
+                    self.context.metrics.connection_accepted();
+                    self.dispatch(conn, permit, forced);
+                }
+                None => {
+                    summary.rejected += 1;
+                    self.context.metrics.connection_rejected();
+                    self.reject(conn);
+                }
+            }
+        }
+        summary.forced = self.drain();
+        self.context.log(&Event::Shutdown {
+            summary: &summary,
+            metrics: self.metrics(),
+        });
+        summary
+    }
+
+    /// 等待已有连接在期限内结束，到期后强制断开剩余的连接，返回强制断开的连接数
+    fn drain(&self) -> usize {
+        let deadline = Instant::now() + self.drain_timeout;
+        let limit = &self.context.limit;
+        let forced = if limit.wait_idle(deadline) {
+            0
+        } else {
+            limit.close_all()
+        };
+        self.pool.join();  // 被断开的连接读取到EOF后，处理任务很快结束
+        forced
+    }
+
+    fn accept(&self) -> Option<Box<dyn Connection>> {
+        match self.listener.accept() {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                eprintln!("failed: {}", e);
+                None
+            }
+        }
+    }
+
+    fn dispatch(&self, conn: Box<dyn Connection>, permit: ConnectionPermit, forced: Arc<AtomicBool>) {
+        let context = self.context.clone();
+        // 任务未被执行就被丢弃时名额随闭包一起归还
+        let result = self.pool.execute(move || {
+            let _permit = permit;
+            handle_client(conn, &context, &forced);
+        });
+        if let Err(e) = result {
+            eprintln!("failed to dispatch connection: {}", e);
+        }
+    }
+
+    /// 回复繁忙信息并关闭连接
+    fn reject(&self, mut conn: Box<dyn Connection>) {
+        let peer = conn.peer();
+        let _ = conn.write_all(BUSY_MESSAGE).and_then(|_| conn.finish());
+        self.context.log(&Event::Rejected {
+            transport: self.context.transport,
+            peer: &peer,
+        });
+    }
+}
+
+/// 处理连接直到关闭，记录连接的统计信息
+fn handle_client(mut conn: Box<dyn Connection>, context: &Context, forced: &AtomicBool) {
+    let started = Instant::now();
+    let peer = conn.peer();  // 获取请求地址
+    let mut session = Session::new(context.limit.clone(), context.metrics.clone());
+    let result = serve(&mut conn, &mut session, context);
+    let close = match result {
+        Ok(Close::Eof) if forced.load(Ordering::SeqCst) => Close::Shutdown.to_string(),
+        Ok(close) => close.to_string(),
+        Err(e) => format!("error: {}", e),
+    };
+    let record = ConnectionRecord {
+        transport: context.transport,
+        peer,
+        bytes_in: session.bytes_in as u64,
+        bytes_out: session.bytes_out as u64,
+        commands: session.commands as u64,
+        duration_ms: started.elapsed().as_millis() as u64,
+        close,
+    };
+    context.metrics.connection_closed();
+    context.log(&Event::Connection(&record));
+}
+
+fn serve(conn: &mut Box<dyn Connection>, session: &mut Session, context: &Context) -> io::Result<Close> {
+    conn.set_idle_timeout(context.idle_timeout)?;  // 超过该时间未收到数据即断开连接
+    let close = match context.mode {
+        Mode::Raw => protocol::serve_raw(conn, session)?,
+        Mode::Line => protocol::serve_lines(conn, session)?,
+    };
+    conn.finish()?;
+    Ok(close)
+}
+
+/// 关闭时默认等待已有连接结束的时间
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// echo服务器构造器，用于配置工作线程数、最大连接数和空闲超时
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    workers: Option<usize>,  // 工作线程数
+    max_connections: Option<usize>,  // 最大连接数
+    overflow: Overflow,  // 连接数达到上限时的处理方式
+    idle_timeout: Option<Duration>,  // 空闲超时时间
+    mode: Mode,  // 连接的处理模式
+    drain_timeout: Option<Duration>,  // 关闭时等待已有连接结束的时间
+    log_file: Option<PathBuf>,  // 事件日志文件
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+    // 配置处理连接的工作线程数，默认为CPU核数
+    pub fn workers(mut self, workers: usize) -> Builder {
+        assert!(workers > 0);
+        self.workers = Some(workers);
+        self
+    }
+    // 配置最大连接数，默认等于工作线程数；大于工作线程数时多出的连接在线程池中排队
+    pub fn max_connections(mut self, max: usize) -> Builder {
+        assert!(max > 0);
+        self.max_connections = Some(max);
+        self
+    }
+    // 配置连接数达到上限时的处理方式，默认排队等待
+    pub fn overflow(mut self, overflow: Overflow) -> Builder {
+        self.overflow = overflow;
+        self
+    }
+    // 配置连接空闲超时时间，默认不超时
+    pub fn idle_timeout(mut self, timeout: Duration) -> Builder {
+        assert!(timeout > Duration::from_secs(0));  // set_read_timeout不接受零
+        self.idle_timeout = Some(timeout);
+        self
+    }
+    // 配置连接的处理模式，默认原样回显
+    pub fn mode(mut self, mode: Mode) -> Builder {
+        self.mode = mode;
+        self
+    }
+    // 配置关闭时等待已有连接结束的时间，到期后强制断开剩余的连接，默认5秒
+    pub fn drain_timeout(mut self, timeout: Duration) -> Builder {
+        self.drain_timeout = Some(timeout);
+        self
+    }
+    // 配置事件日志文件，连接结束、连接被拒绝和服务器关闭时以JSON lines格式追加写入，默认不记录
+    pub fn log_file<P: Into<PathBuf>>(mut self, path: P) -> Builder {
+        self.log_file = Some(path.into());
+        self
+    }
+    // 绑定TCP地址并创建服务器
+    pub fn bind<A: ToSocketAddrs>(self, addr: A) -> io::Result<Server> {
+        let addr = resolve(addr)?;
+        self.listen(&Endpoint::Tcp(addr))
+    }
+    // 绑定UDP地址并创建服务器，每个数据报作为一次独立的请求处理
+    pub fn bind_udp<A: ToSocketAddrs>(self, addr: A) -> io::Result<Server> {
+        let addr = resolve(addr)?;
+        self.listen(&Endpoint::Udp(addr))
+    }
+    // 在指定路径创建Unix域socket并创建服务器，服务器释放时删除socket文件
+    pub fn bind_unix<P: AsRef<Path>>(self, path: P) -> io::Result<Server> {
+        self.listen(&Endpoint::Unix(path.as_ref().to_owned()))
+    }
+    // 按地址的传输方式绑定并创建服务器
+    pub fn listen(self, endpoint: &Endpoint) -> io::Result<Server> {
+        let log = match self.log_file {
+            Some(ref path) => Some(EventLog::open(path)?),
+            None => None,
+        };
+        let listener = transport::bind(endpoint)?;
+        let pool = match self.workers {
+            Some(workers) => thread_pool::Builder::new().num_threads(workers),
+            None => thread_pool::Builder::new(),
+        }.thread_name("echo-worker".to_string()).build();
+        let max_connections = self.max_connections
+            .unwrap_or_else(|| pool.max_count());
+        Ok(Server {
+            listener,
+            pool,
+            context: Arc::new(Context {
+                transport: endpoint.transport(),
+                mode: self.mode,
+                idle_timeout: self.idle_timeout,
+                limit: ConnectionLimit::new(max_connections),
+                metrics: Arc::new(ServerMetrics::default()),
+                log,
+            }),
+            overflow: self.overflow,
+            drain_timeout: self.drain_timeout.unwrap_or(DEFAULT_DRAIN_TIMEOUT),
+            shutdown: ShutdownHandle::default(),
+        })
+    }
+}
+
+/// 取解析出的第一个地址
+fn resolve<A: ToSocketAddrs>(addr: A) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "could not resolve address"))
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// 用于从其他线程（如信号处理函数或测试）关闭服务器的句柄
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl ShutdownHandle {
+    /// 请求关闭服务器：停止接受新连接，等待已有连接结束后`Server::run`返回
+    pub fn shutdown(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+    /// 是否已请求关闭
+    pub fn is_shutdown(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
+
+/// 服务器关闭后的统计信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct Summary {
+    pub served: usize,  // 处理的连接数
+    pub rejected: usize,  // 因连接数达到上限被拒绝的连接数
+    pub forced: usize,  // 到关闭期限时仍未结束、被强制断开的连接数
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "served {} connections ({} rejected, {} closed at shutdown deadline)",
+               self.served, self.rejected, self.forced)
+    }
+}
+
+/// 等待文件描述符可读，超时返回false；用于让accept可以被关闭请求打断
+pub(crate) fn wait_readable<T: AsRawFd + ?Sized>(fd: &T, timeout: Duration) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd: fd.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+    match unsafe { libc::poll(&mut pollfd, 1, timeout) } {
+        -1 => {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {  // 被信号打断，视为超时
+                Ok(false)
+            } else {
+                Err(error)
+            }
+        }
+        0 => Ok(false),
+        _ => Ok(true),
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::limit::Closer;
+
+/// UDP数据报的最大长度
+const MAX_DATAGRAM_LEN: usize = 64 * 1024;
+
+/// 服务器监听的地址及传输方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(SocketAddr),  // TCP连接
+    Udp(SocketAddr),  // UDP数据报，每个数据报作为一次独立的请求
+    Unix(PathBuf),  // Unix域流式socket
+}
+
+impl Endpoint {
+    /// 传输方式的名称，用于日志
+    pub(crate) fn transport(&self) -> &'static str {
+        match *self {
+            Endpoint::Tcp(_) => "tcp",
+            Endpoint::Udp(_) => "udp",
+            Endpoint::Unix(_) => "unix",
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Endpoint::Tcp(ref addr) => write!(f, "tcp://{}", addr),
+            Endpoint::Udp(ref addr) => write!(f, "udp://{}", addr),
+            Endpoint::Unix(ref path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// 服务器接受的一个连接，各种传输方式共用同一套处理逻辑
+pub(crate) trait Connection: Read + Write + Send {
+    /// 对端地址，用于日志
+    fn peer(&self) -> String;
+    /// 设置空闲超时，超过该时间未收到数据时读取返回超时错误
+    fn set_idle_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+    /// 创建关闭连接的回调，关闭后连接上阻塞的读取立即返回EOF
+    fn closer(&self) -> io::Result<Closer>;
+    /// 处理结束时调用，用于发送缓存的回复
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 接受连接的监听者，文件描述符用于等待新连接时响应关闭请求
+pub(crate) trait Listener: AsRawFd + Send {
+    fn accept(&self) -> io::Result<Box<dyn Connection>>;
+    fn local_endpoint(&self) -> io::Result<Endpoint>;
+}
+
+/// 绑定地址并创建对应传输方式的监听者
+pub(crate) fn bind(endpoint: &Endpoint) -> io::Result<Box<dyn Listener>> {
+    Ok(match *endpoint {
+        Endpoint::Tcp(ref addr) => Box::new(TcpListener::bind(addr)?),
+        Endpoint::Udp(ref addr) => Box::new(UdpListener(Arc::new(UdpSocket::bind(addr)?))),
+        Endpoint::Unix(ref path) => Box::new(UnixSocketListener {
+            listener: UnixListener::bind(path)?,
+            path: path.clone(),
+        }),
+    })
+}
+
+impl Listener for TcpListener {
+    fn accept(&self) -> io::Result<Box<dyn Connection>> {
+        Ok(Box::new(TcpListener::accept(self)?.0))
+    }
+    fn local_endpoint(&self) -> io::Result<Endpoint> {
+        Ok(Endpoint::Tcp(self.local_addr()?))
+    }
+}
+
+impl Connection for TcpStream {
+    fn peer(&self) -> String {
+        match self.peer_addr() {
+            Ok(addr) => addr.to_string(),
+            Err(_) => "unknown".to_string(),
+        }
+    }
+    fn set_idle_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)
+    }
+    fn closer(&self) -> io::Result<Closer> {
+        let stream = self.try_clone()?;
+        Ok(Box::new(move || {
+            let _ = stream.shutdown(Shutdown::Both);
+        }))
+    }
+}
+
+/// Unix域socket监听者，释放时删除socket文件
+struct UnixSocketListener {
+    listener: UnixListener,
+    path: PathBuf,  // socket文件路径
+}
+
+impl AsRawFd for UnixSocketListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+}
+
+impl Listener for UnixSocketListener {
+    fn accept(&self) -> io::Result<Box<dyn Connection>> {
+        Ok(Box::new(self.listener.accept()?.0))
+    }
+    fn local_endpoint(&self) -> io::Result<Endpoint> {
+        Ok(Endpoint::Unix(self.path.clone()))
+    }
+}
+
+impl Drop for UnixSocketListener {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Connection for UnixStream {
+    fn peer(&self) -> String {
+        match self.peer_addr().ok().and_then(|addr| addr.as_pathname().map(|path| path.to_owned())) {
+            Some(path) => path.display().to_string(),
+            None => "unix:unnamed".to_string(),  // 客户端通常不绑定地址
+        }
+    }
+    fn set_idle_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)
+    }
+    fn closer(&self) -> io::Result<Closer> {
+        let stream = self.try_clone()?;
+        Ok(Box::new(move || {
+            let _ = stream.shutdown(Shutdown::Both);
+        }))
+    }
+}
+
+/// UDP监听者，每收到一个数据报即视为一个连接
+struct UdpListener(Arc<UdpSocket>);
+
+impl AsRawFd for UdpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl Listener for UdpListener {
+    fn accept(&self) -> io::Result<Box<dyn Connection>> {
+        let mut buf = vec![0; MAX_DATAGRAM_LEN];
+        let (len, peer) = self.0.recv_from(&mut buf)?;
+        buf.truncate(len);
+        Ok(Box::new(Datagram {
+            socket: self.0.clone(),
+            peer,
+            input: io::Cursor::new(buf),
+            output: Vec::new(),
+        }))
+    }
+    fn local_endpoint(&self) -> io::Result<Endpoint> {
+        Ok(Endpoint::Udp(self.0.local_addr()?))
+    }
+}
+
+/// 一个UDP数据报：读取数据报的内容后即为EOF，写入的回复在处理结束时作为一个数据报发回
+struct Datagram {
+    socket: Arc<UdpSocket>,
+    peer: SocketAddr,  // 发送方地址
+    input: io::Cursor<Vec<u8>>,  // 收到的数据
+    output: Vec<u8>,  // 缓存的回复
+}
+
+impl Read for Datagram {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for Datagram {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Connection for Datagram {
+    fn peer(&self) -> String {
+        self.peer.to_string()
+    }
+    fn set_idle_timeout(&self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())  // 数据报已完整收到，读取不会阻塞
+    }
+    fn closer(&self) -> io::Result<Closer> {
+        Ok(Box::new(|| {}))
+    }
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.output.is_empty() {
+            self.socket.send_to(&self.output, self.peer)?;
+            self.output.clear();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use echo::{CliError, Endpoint, Mode, Options, Overflow};
+
+#[test]
+fn defaults_to_tcp_on_localhost() {
+    let options = Options::parse(Vec::<String>::new()).unwrap();
+    assert_eq!(Endpoint::Tcp("127.0.0.1:8080".parse().unwrap()), options.endpoint);
+    assert_eq!(Mode::Raw, options.mode);
+    assert_eq!(Overflow::Queue, options.overflow);
+    assert_eq!(None, options.workers);
+}
+
+#[test]
+fn parses_transports_and_options() {
+    let options = Options::parse(vec!["--transport", "udp", "--bind=0.0.0.0:9000", "--line"]).unwrap();
+    assert_eq!(Endpoint::Udp("0.0.0.0:9000".parse().unwrap()), options.endpoint);
+    assert_eq!(Mode::Line, options.mode);
+
+    let options = Options::parse(vec!["-t", "unix"]).unwrap();
+    assert_eq!(Endpoint::Unix(PathBuf::from("/tmp/echo.sock")), options.endpoint);
+
+    let options = Options::parse(vec![
+        "-t", "unix", "-b", "/run/echo.sock", "--workers", "4", "--max-connections", "16",
+        "--reject", "--idle-timeout", "1.5", "--drain-timeout=2", "--log", "/var/log/echo.jsonl",
+    ]).unwrap();
+    assert_eq!(Endpoint::Unix(PathBuf::from("/run/echo.sock")), options.endpoint);
+    assert_eq!(Some(4), options.workers);
+    assert_eq!(Some(16), options.max_connections);
+    assert_eq!(Overflow::Reject, options.overflow);
+    assert_eq!(Some(Duration::from_millis(1500)), options.idle_timeout);
+    assert_eq!(Some(Duration::from_secs(2)), options.drain_timeout);
+    assert_eq!(Some(PathBuf::from("/var/log/echo.jsonl")), options.log_file);
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    assert_eq!(Err(CliError::Help), Options::parse(vec!["--help"]).map(|_| ()));
+    let invalid = |args: Vec<&str>| match Options::parse(args) {
+        Err(CliError::Invalid(message)) => message,
+        other => panic!("expected an error, got {:?}", other),
+    };
+    assert_eq!("unknown transport 'sctp'", invalid(vec!["-t", "sctp"]));
+    assert_eq!("missing value for '--bind'", invalid(vec!["--bind"]));
+    assert_eq!("unknown option '--verbose'", invalid(vec!["--verbose"]));
+    assert_eq!("'--workers' expects a positive integer, got '0'", invalid(vec!["--workers", "0"]));
+    assert_eq!("invalid address 'nowhere'", invalid(vec!["--bind", "nowhere"]));
+    assert_eq!("'--idle-timeout' expects a positive number of seconds, got '1e20'",
+               invalid(vec!["--idle-timeout", "1e20"]));
+    assert_eq!("'--drain-timeout' expects a positive number of seconds, got 'inf'",
+               invalid(vec!["--drain-timeout=inf"]));
+    assert_eq!("'--idle-timeout' expects a positive number of seconds, got '1e-10'",
+               invalid(vec!["--idle-timeout", "1e-10"]));
+    assert_eq!("'--drain-timeout' expects a positive number of seconds, got '-1'",
+               invalid(vec!["--drain-timeout=-1"]));
+    assert_eq!("'--line' does not take a value", invalid(vec!["--line=x"]));
+    assert_eq!("'--reject' does not take a value", invalid(vec!["--reject=false"]));
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use echo::{Builder, Metrics, Mode, Overflow};
+use serde_json::Value;
+
+fn log_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("echo-test-{}-{}.jsonl", name, process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn read_log(path: &PathBuf) -> Vec<Value> {
+    fs::read_to_string(path).unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn stats_include_server_totals() {
+    let server = Builder::new().workers(2).mode(Mode::Line).bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || {
+        let summary = server.run();
+        (summary, server.metrics())
+    });
+    let mut first = TcpStream::connect(addr).unwrap();
+    first.write_all(b"ECHO hi\nQUIT\n").unwrap();
+    let mut replies = String::new();
+    first.read_to_string(&mut replies).unwrap();
+    assert_eq!("OK hi\r\nOK bye\r\n", replies);
+    thread::sleep(Duration::from_millis(50));
+
+    let mut second = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(second.try_clone().unwrap());
+    second.write_all(b"STATS\n").unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!("OK connections=1 commands=1 bytes_in=6 bytes_out=0 \
+                accepted=2 rejected=0 closed=1 total_bytes_in=19 total_bytes_out=15\r\n", line);
+    drop(reader);
+    drop(second);
+    shutdown.shutdown();
+    let (_, metrics) = runner.join().unwrap();
+    assert_eq!(Metrics {
+        accepted: 2,
+        rejected: 0,
+        active: 0,
+        closed: 2,
+        bytes_in: 19,
+        bytes_out: 15 + line.len() as u64,
+    }, metrics);
+}
+
+#[test]
+fn writes_connection_records_as_json_lines() {
+    let path = log_path("records");
+    let server = Builder::new().workers(1).max_connections(1).overflow(Overflow::Reject)
+        .drain_timeout(Duration::from_millis(100))
+        .log_file(&path)
+        .bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || server.run());
+    let mut client = TcpStream::connect(addr).unwrap();
+    let local = client.local_addr().unwrap().to_string();
+    client.write_all(b"hello").unwrap();
+    let mut buf = [0; 5];
+    client.read_exact(&mut buf).unwrap();
+    let mut rejected = TcpStream::connect(addr).unwrap();
+    let mut reply = String::new();
+    rejected.read_to_string(&mut reply).unwrap();
+    shutdown.shutdown();  // 未关闭的连接在期限到达后被强制断开
+    let summary = runner.join().unwrap();
+    assert_eq!(1, summary.forced);
+
+    let events = read_log(&path);
+    assert_eq!(3, events.len());
+    assert_eq!("rejected", events[0]["event"]);
+    assert_eq!("tcp", events[0]["transport"]);
+    assert_eq!("connection", events[1]["event"]);
+    assert_eq!(local, events[1]["peer"]);
+    assert_eq!(5, events[1]["bytes_in"]);
+    assert_eq!(5, events[1]["bytes_out"]);
+    assert_eq!("shutdown", events[1]["close"]);
+    assert!(events[1]["duration_ms"].as_u64().unwrap() >= 100);
+    assert!(events[1]["timestamp"].as_f64().unwrap() > 0.0);
+    assert_eq!("shutdown", events[2]["event"]);
+    assert_eq!(1, events[2]["summary"]["served"]);
+    assert_eq!(1, events[2]["metrics"]["rejected"]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn records_close_reasons() {
+    let path = log_path("reasons");
+    let server = Builder::new().workers(2).mode(Mode::Line)
+        .idle_timeout(Duration::from_millis(100))
+        .log_file(&path)
+        .bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || server.run());
+    let mut quit = TcpStream::connect(addr).unwrap();
+    quit.write_all(b"QUIT\n").unwrap();
+    let mut rest = String::new();
+    quit.read_to_string(&mut rest).unwrap();
+    let idle = TcpStream::connect(addr).unwrap();
+    thread::sleep(Duration::from_millis(300));
+    drop(idle);
+    let eof = TcpStream::connect(addr).unwrap();
+    drop(eof);
+    thread::sleep(Duration::from_millis(50));
+    shutdown.shutdown();
+    runner.join().unwrap();
+
+    let closes: Vec<String> = read_log(&path).iter()
+        .filter(|event| event["event"] == "connection")
+        .map(|event| event["close"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(vec!["quit", "idle timeout", "eof"], closes);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,88 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use echo::{Builder, Mode};
+
+/// 以行模式启动服务器并建立连接
+fn connect() -> (TcpStream, BufReader<TcpStream>) {
+    let server = Builder::new().workers(2).mode(Mode::Line).bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    let stream = TcpStream::connect(addr).unwrap();
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    (stream, reader)
+}
+
+fn read_line(reader: &mut BufReader<TcpStream>) -> String {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    line
+}
+
+fn command(stream: &mut TcpStream, reader: &mut BufReader<TcpStream>, line: &str) -> String {
+    stream.write_all(line.as_bytes()).unwrap();
+    read_line(reader)
+}
+
+#[test]
+fn echo_and_upper() {
+    let (mut stream, mut reader) = connect();
+    assert_eq!("OK hello world\r\n", command(&mut stream, &mut reader, "ECHO hello world\n"));
+    assert_eq!("OK HELLO\r\n", command(&mut stream, &mut reader, "upper hello\r\n"));
+    assert_eq!("OK\r\n", command(&mut stream, &mut reader, "ECHO\n"));
+}
+
+#[test]
+fn buffers_partial_lines() {
+    let (mut stream, mut reader) = connect();
+    stream.write_all(b"ECHO spl").unwrap();
+    stream.flush().unwrap();
+    thread::sleep(std::time::Duration::from_millis(50));
+    stream.write_all(b"it\nUPPER a\n\nECHO b\n").unwrap();
+    assert_eq!("OK split\r\n", read_line(&mut reader));
+    assert_eq!("OK A\r\n", read_line(&mut reader));  // 空行被忽略
+    assert_eq!("OK b\r\n", read_line(&mut reader));
+}
+
+#[test]
+fn executes_final_line_without_newline_at_eof() {
+    let (mut stream, mut reader) = connect();
+    stream.write_all(b"ECHO a\nUPPER last").unwrap();
+    stream.shutdown(Shutdown::Write).unwrap();  // 半关闭，服务器读到EOF
+    assert_eq!("OK a\r\n", read_line(&mut reader));
+    assert_eq!("OK LAST\r\n", read_line(&mut reader));
+    assert_eq!("", read_line(&mut reader));  // 执行后关闭连接
+}
+
+#[test]
+fn time_and_stats() {
+    let (mut stream, mut reader) = connect();
+    let reply = command(&mut stream, &mut reader, "TIME\n");
+    let seconds: f64 = reply.trim_end().strip_prefix("OK ").unwrap().parse().unwrap();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+    assert!((now - seconds).abs() < 5.0);
+    let reply = command(&mut stream, &mut reader, "STATS\n");
+    assert_eq!("OK connections=1 commands=2 bytes_in=11 bytes_out=", &reply[..50]);
+}
+
+#[test]
+fn unknown_command_and_quit() {
+    let (mut stream, mut reader) = connect();
+    assert_eq!("ERR unknown command 'PING'\r\n", command(&mut stream, &mut reader, "PING\n"));
+    assert_eq!("OK bye\r\n", command(&mut stream, &mut reader, "QUIT\n"));
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();  // 服务器已关闭连接
+    assert_eq!("", rest);
+}
+
+#[test]
+fn rejects_overlong_lines() {
+    let (mut stream, mut reader) = connect();
+    stream.write_all(&[b'a'; 9 * 1024]).unwrap();  // 恰好在服务器断开前被全部读取，避免连接被重置
+    assert_eq!("ERR line too long\r\n", read_line(&mut reader));
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!("", rest);
+}
@@ -0,0 +1,75 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use echo::{Builder, Overflow};
+
+/// 在后台线程中运行服务器，返回其地址
+fn start(builder: Builder) -> SocketAddr {
+    let server = builder.bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    addr
+}
+
+fn echo(stream: &mut TcpStream, data: &[u8]) -> Vec<u8> {
+    stream.write_all(data).unwrap();
+    let mut buf = vec![0; data.len()];
+    stream.read_exact(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn echoes_data() {
+    let addr = start(Builder::new().workers(2));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    assert_eq!(b"hello".to_vec(), echo(&mut stream, b"hello"));
+    let large = vec![7u8; 10_000];
+    assert_eq!(large, echo(&mut stream, &large));
+}
+
+#[test]
+fn rejects_connections_over_limit() {
+    let addr = start(Builder::new().workers(1).max_connections(1).overflow(Overflow::Reject));
+    let mut first = TcpStream::connect(addr).unwrap();
+    assert_eq!(b"a".to_vec(), echo(&mut first, b"a"));
+    let mut second = TcpStream::connect(addr).unwrap();
+    let mut reply = String::new();
+    second.read_to_string(&mut reply).unwrap();
+    assert_eq!("ERR server busy\r\n", reply);
+    drop(first);
+    thread::sleep(Duration::from_millis(50));
+    let mut third = TcpStream::connect(addr).unwrap();
+    assert_eq!(b"c".to_vec(), echo(&mut third, b"c"));
+}
+
+#[test]
+fn queues_connections_over_limit() {
+    let addr = start(Builder::new().workers(1).max_connections(1));
+    let mut first = TcpStream::connect(addr).unwrap();
+    assert_eq!(b"a".to_vec(), echo(&mut first, b"a"));
+    let mut second = TcpStream::connect(addr).unwrap();
+    second.write_all(b"b").unwrap();
+    second.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+    let mut buf = [0; 1];
+    let error = second.read(&mut buf).unwrap_err();
+    assert!(error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::TimedOut);
+    drop(first);  // 第一个连接关闭后第二个连接才被处理
+    second.set_read_timeout(None).unwrap();
+    second.read_exact(&mut buf).unwrap();
+    assert_eq!(b"b", &buf);
+}
+
+#[test]
+fn closes_idle_connections() {
+    let addr = start(Builder::new().workers(1).idle_timeout(Duration::from_millis(100)));
+    let mut stream = TcpStream::connect(addr).unwrap();
+    assert_eq!(b"a".to_vec(), echo(&mut stream, b"a"));
+    let start = Instant::now();
+    let mut buf = [0; 1];
+    assert_eq!(0, stream.read(&mut buf).unwrap());  // 服务器因空闲超时关闭连接
+    assert!(start.elapsed() >= Duration::from_millis(90));
+    let mut other = TcpStream::connect(addr).unwrap();  // 超时的连接释放了工作线程
+    assert_eq!(b"b".to_vec(), echo(&mut other, b"b"));
+}
@@ -0,0 +1,68 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use echo::{Builder, Overflow, Summary};
+
+#[test]
+fn shutdown_without_connections() {
+    let server = Builder::new().workers(1).bind("127.0.0.1:0").unwrap();
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || server.run());
+    thread::sleep(Duration::from_millis(50));
+    let start = Instant::now();
+    shutdown.shutdown();
+    assert!(shutdown.is_shutdown());
+    assert_eq!(Summary::default(), runner.join().unwrap());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn drains_connections_before_deadline() {
+    let server = Builder::new().workers(2).drain_timeout(Duration::from_secs(5))
+        .bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || server.run());
+    let mut first = TcpStream::connect(addr).unwrap();
+    let mut second = TcpStream::connect(addr).unwrap();
+    first.write_all(b"a").unwrap();
+    let mut buf = [0; 1];
+    first.read_exact(&mut buf).unwrap();
+    drop(first);
+    shutdown.shutdown();
+    thread::sleep(Duration::from_millis(200));
+    second.write_all(b"b").unwrap();  // 关闭期间已有连接仍被正常处理
+    second.read_exact(&mut buf).unwrap();
+    assert_eq!(b"b", &buf);
+    drop(second);
+    let summary = runner.join().unwrap();
+    assert_eq!(Summary { served: 2, rejected: 0, forced: 0 }, summary);
+}
+
+#[test]
+fn forces_connections_closed_at_deadline() {
+    let server = Builder::new().workers(1).max_connections(2).overflow(Overflow::Reject)
+        .drain_timeout(Duration::from_millis(200))
+        .bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || server.run());
+    let mut active = TcpStream::connect(addr).unwrap();
+    let mut queued = TcpStream::connect(addr).unwrap();  // 唯一的工作线程被占用，该连接在线程池中排队
+    let mut rejected = TcpStream::connect(addr).unwrap();
+    let mut reply = String::new();
+    rejected.read_to_string(&mut reply).unwrap();
+    assert_eq!("ERR server busy\r\n", reply);
+    active.write_all(b"a").unwrap();
+    let mut buf = [0; 1];
+    active.read_exact(&mut buf).unwrap();
+    let start = Instant::now();
+    shutdown.shutdown();
+    let summary = runner.join().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert_eq!(Summary { served: 2, rejected: 1, forced: 2 }, summary);
+    assert_eq!(0, active.read(&mut buf).unwrap());
+    assert_eq!(0, queued.read(&mut buf).unwrap());
+}
@@ -0,0 +1,96 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use echo::{Builder, Endpoint, Mode};
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("echo-test-{}-{}.sock", name, process::id()))
+}
+
+fn udp_client(server: SocketAddr) -> UdpSocket {
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    client.connect(server).unwrap();
+    client
+}
+
+#[test]
+fn udp_echoes_datagrams() {
+    let server = Builder::new().workers(2).bind_udp("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    assert_eq!(Endpoint::Udp(addr), server.local_endpoint().unwrap());
+    thread::spawn(move || server.run());
+    let client = udp_client(addr);
+    let large = vec![7u8; 4000];
+    for data in [&b"hello"[..], &large[..]] {
+        client.send(data).unwrap();
+        let mut buf = vec![0; 8192];
+        let len = client.recv(&mut buf).unwrap();
+        assert_eq!(data, &buf[..len]);  // 回复作为一个完整的数据报发回
+    }
+}
+
+#[test]
+fn udp_line_mode_replies_per_datagram() {
+    let server = Builder::new().workers(1).mode(Mode::Line).bind_udp("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    let client = udp_client(addr);
+    client.send(b"ECHO a\nUPPER b\nNOPE\n").unwrap();
+    let mut buf = [0; 1024];
+    let len = client.recv(&mut buf).unwrap();
+    assert_eq!("OK a\r\nOK B\r\nERR unknown command 'NOPE'\r\n", String::from_utf8_lossy(&buf[..len]));
+}
+
+#[test]
+fn udp_line_mode_accepts_command_without_newline() {
+    let server = Builder::new().workers(1).mode(Mode::Line).bind_udp("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    let client = udp_client(addr);
+    let mut buf = [0; 1024];
+    client.send(b"ECHO hi").unwrap();
+    let len = client.recv(&mut buf).unwrap();
+    assert_eq!("OK hi\r\n", String::from_utf8_lossy(&buf[..len]));
+    client.send(b"ECHO a\nUPPER b").unwrap();  // 只有最后一行没有换行
+    let len = client.recv(&mut buf).unwrap();
+    assert_eq!("OK a\r\nOK B\r\n", String::from_utf8_lossy(&buf[..len]));
+}
+
+#[test]
+fn unix_socket_serves_lines() {
+    let path = socket_path("lines");
+    let server = Builder::new().workers(1).mode(Mode::Line).bind_unix(&path).unwrap();
+    assert_eq!(Endpoint::Unix(path.clone()), server.local_endpoint().unwrap());
+    assert!(server.local_addr().is_err());
+    let shutdown = server.shutdown_handle();
+    let runner = thread::spawn(move || server.run());
+    let mut stream = UnixStream::connect(&path).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    stream.write_all(b"ECHO over unix\nSTATS\n").unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!("OK over unix\r\n", line);
+    line.clear();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.starts_with("OK connections=1 commands=2"));
+    drop(reader);
+    drop(stream);
+    shutdown.shutdown();
+    assert_eq!(1, runner.join().unwrap().served);
+    assert!(!path.exists());  // 服务器释放时删除socket文件
+}
+
+#[test]
+fn unix_socket_refuses_existing_path() {
+    let path = socket_path("existing");
+    let first = Builder::new().workers(1).bind_unix(&path).unwrap();
+    assert!(Builder::new().workers(1).bind_unix(&path).is_err());
+    drop(first);
+    assert!(!path.exists());
+}
@@ -0,0 +1,141 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use thread_pool::ThreadPool;
+
+const NUM_THREADS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// 原有设计：所有工作线程争抢同一个Mutex<Receiver>
+struct ChannelPool {
+    jobs: Option<Sender<Job>>,
+    pending: Arc<(Mutex<usize>, Condvar)>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl ChannelPool {
+    fn new(num_threads: usize) -> ChannelPool {
+        let (tx, rx) = channel::<Job>();
+        let rx: Arc<Mutex<Receiver<Job>>> = Arc::new(Mutex::new(rx));
+        let pending = Arc::new((Mutex::new(0), Condvar::new()));
+        let workers = (0..num_threads)
+            .map(|_| {
+                let rx = rx.clone();
+                let pending = pending.clone();
+                thread::spawn(move || loop {
+                    let message = rx.lock().unwrap().recv();
+                    let job = match message {
+                        Ok(job) => job,
+                        Err(..) => break,
+                    };
+                    job();
+                    let (ref lock, ref condvar) = *pending;
+                    let mut count = lock.lock().unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        condvar.notify_all();
+                    }
+                })
+            })
+            .collect();
+        ChannelPool { jobs: Some(tx), pending, workers }
+    }
+
+    fn execute<F>(&self, job: F) where F: FnOnce() + Send + 'static {
+        *self.pending.0.lock().unwrap() += 1;
+        self.jobs.as_ref().unwrap().send(Box::new(job)).unwrap();
+    }
+
+    fn join(&self) {
+        let (ref lock, ref condvar) = *self.pending;
+        let mut count = lock.lock().unwrap();
+        while *count > 0 {
+            count = condvar.wait(count).unwrap();
+        }
+    }
+}
+
+impl Drop for ChannelPool {
+    fn drop(&mut self) {
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+fn tiny_jobs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tiny_jobs");
+    for &jobs in &[1_000usize, 10_000] {
+        group.throughput(Throughput::Elements(jobs as u64));
+
+        let pool = ThreadPool::new(NUM_THREADS);
+        let counter = Arc::new(AtomicUsize::new(0));
+        group.bench_with_input(BenchmarkId::new("work_stealing", jobs), &jobs, |b, &jobs| {
+            b.iter(|| {
+                for _ in 0..jobs {
+                    let counter = counter.clone();
+                    pool.execute(move || {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }).unwrap();
+                }
+                pool.join();
+            })
+        });
+
+        let pool = ChannelPool::new(NUM_THREADS);
+        group.bench_with_input(BenchmarkId::new("mutex_receiver", jobs), &jobs, |b, &jobs| {
+            b.iter(|| {
+                for _ in 0..jobs {
+                    let counter = counter.clone();
+                    pool.execute(move || {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+                pool.join();
+            })
+        });
+    }
+    group.finish();
+}
+
+/// 在工作线程中提交任务，工作窃取设计下进入本地队列
+fn nested_jobs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nested_jobs");
+    let jobs = 10_000usize;
+    group.throughput(Throughput::Elements(jobs as u64));
+
+    let pool = Arc::new(ThreadPool::new(NUM_THREADS));
+    group.bench_function("work_stealing", |b| {
+        b.iter(|| {
+            let inner_pool = pool.clone();
+            pool.execute(move || {
+                for _ in 0..jobs {
+                    inner_pool.execute(|| {}).unwrap();
+                }
+            }).unwrap();
+            pool.join();
+        })
+    });
+
+    let pool = Arc::new(ChannelPool::new(NUM_THREADS));
+    group.bench_function("mutex_receiver", |b| {
+        b.iter(|| {
+            let inner_pool = pool.clone();
+            pool.execute(move || {
+                for _ in 0..jobs {
+                    inner_pool.execute(|| {});
+                }
+            });
+            pool.join();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, tiny_jobs, nested_jobs);
+criterion_main!(benches);
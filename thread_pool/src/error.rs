@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::fmt;
+
+/// 提交任务失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecuteError {
+    QueueFull,  // 队列已满且策略为拒绝
+    Shutdown,  // 线程池已关闭
+}
+
+impl fmt::Display for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExecuteError::QueueFull => write!(f, "job queue is full"),
+            ExecuteError::Shutdown => write!(f, "thread pool has been shut down"),
+        }
+    }
+}
+
+impl Error for ExecuteError {}
+
+/// `try_execute`提交任务失败的原因，携带未能提交的任务
+pub enum TryExecuteError<F> {
+    Full(F),  // 队列已满
+    Shutdown(F),  // 线程池已关闭
+}
+
+impl<F> TryExecuteError<F> {
+    /// 取回未能提交的任务
+    pub fn into_inner(self) -> F {
+        match self {
+            TryExecuteError::Full(job) | TryExecuteError::Shutdown(job) => job,
+        }
+    }
+}
+
+impl<F> fmt::Debug for TryExecuteError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TryExecuteError::Full(..) => f.write_str("Full(..)"),
+            TryExecuteError::Shutdown(..) => f.write_str("Shutdown(..)"),
+        }
+    }
+}
+
+impl<F> fmt::Display for TryExecuteError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TryExecuteError::Full(..) => write!(f, "job queue is full"),
+            TryExecuteError::Shutdown(..) => write!(f, "thread pool has been shut down"),
+        }
+    }
+}
+
+impl<F> Error for TryExecuteError<F> {}
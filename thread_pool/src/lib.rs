@@ -0,0 +1,796 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Condvar, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_deque::{Injector, Stealer, Worker as Deque};
+
+mod error;
+mod panic_info;
+mod par;
+mod queue;
+mod scope;
+mod stats;
+mod task;
+mod timer;
+
+pub use error::{ExecuteError, TryExecuteError};
+pub use panic_info::PanicInfo;
+pub use scope::Scope;
+pub use stats::{ThreadPoolStats, WorkerStats};
+pub use task::{TaskError, TaskHandle};
+pub use timer::PeriodicHandle;
+
+use panic_info::PanicHandler;
+use timer::Timer;
+
+trait FnBox {
+    fn call_box(self: Box<Self>);
+}
+
+impl<F> FnBox for F where F: FnOnce() {
+    fn call_box(self: Box<Self>) {
+        (*self) ()
+    }
+}
+
+type Thunk<'a> = Box<dyn FnBox + Send + 'a>;
+
+/// 工作线程启动或退出时调用的回调，参数为工作线程编号
+type ThreadHook = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// 工作线程找不到任务时，进入休眠前重试的次数
+const IDLE_SPIN_ROUNDS: usize = 32;
+
+/// 队列已满时提交任务的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueueFullPolicy {
+    #[default]
+    Block,  // 阻塞提交任务的线程，直到队列有空位
+    Reject,  // 拒绝任务并返回错误
+    CallerRuns,  // 在提交任务的线程上直接执行
+}
+
+/// 任务优先级，工作线程总是先执行高优先级的任务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    High,  // 高优先级，如需要及时处理的维护任务
+    #[default]
+    Normal,  // 普通优先级，`execute`提交的任务
+    Low,  // 低优先级，只在没有其他任务时执行
+}
+
+/// 单个工作线程的状态，恐慌后重新拉起的线程沿用原有状态
+struct Worker {
+    index: usize,  // 工作线程编号
+    jobs_completed: AtomicUsize,  // 该线程正常执行完毕的任务数
+    stealer: Stealer<Thunk<'static>>,  // 供其他线程窃取该线程本地队列中的任务
+}
+
+struct ThreadPoolSharedData {
+    name: Option<String>,  // 标记线程名称
+    numbered_names: bool,  // 是否在线程名称后追加工作线程编号
+    injectors: [Injector<Thunk<'static>>; 3],  // 按优先级划分的全局队列，接收从线程池外部提交的任务
+    sleep_trigger: Mutex<()>,  // 空闲工作线程在此休眠
+    sleep_condvar: Condvar,  // 有新任务或需要退出时唤醒休眠的工作线程
+    sleeping_count: AtomicUsize,  // 正在休眠的工作线程数
+    closed: AtomicBool,  // 线程池是否已关闭或被释放，关闭后不再接收新任务
+    threads: Mutex<Vec<thread::JoinHandle<()>>>,  // 工作线程和定时线程的句柄，用于关闭时等待线程退出
+    timer: Timer,  // 延迟任务和周期任务的定时器
+    empty_trigger: Mutex<()>,  // 空锁，用于实现线程池的join
+    empty_condvar: Condvar,  // 空条件变量，用于实现线程池的join
+    queue_capacity: Option<usize>,  // 队列容量，None表示无界
+    queue_full_policy: QueueFullPolicy,  // 队列已满时的处理策略
+    space_trigger: Mutex<()>,  // 用于在队列已满时阻塞提交任务的线程
+    space_condvar: Condvar,  // 队列出现空位时通知提交任务的线程
+    queued_count: AtomicUsize,  // 代表线程池中总队列数，多线程用原子类型保证原子性
+    active_count: AtomicUsize,  // 正在执行任务的工作线程数
+    max_thread_count: AtomicUsize,  // 线程池允许的最大线程数
+    thread_count: AtomicUsize,  // 当前存活的工作线程数
+    panic_count: AtomicUsize,  // 记录发生恐慌的工作线程数
+    completed_count: AtomicUsize,  // 线程池正常执行完毕的任务总数
+    panic_handler: Option<PanicHandler>,  // 工作线程中任务恐慌时调用的处理函数
+    panics: Mutex<Vec<PanicInfo>>,  // 上次join以来工作线程中发生的任务恐慌
+    on_thread_start: Option<ThreadHook>,  // 工作线程启动时调用，恐慌后重新拉起的线程也会调用
+    on_thread_stop: Option<ThreadHook>,  // 工作线程退出时调用，包括因恐慌退出
+    workers: RwLock<Vec<Arc<Worker>>>,  // 存活的工作线程
+    next_worker_index: AtomicUsize,  // 下一个工作线程的编号
+    stack_size: Option<usize>,  // 设置工作线程栈大小，默认8MB
+}
+
+impl ThreadPoolSharedData {
+    /// 判断线程池是否正在工作
+    fn has_work(&self) -> bool {
+        self.queued_count.load(Ordering::SeqCst) > 0 ||
+        self.active_count.load(Ordering::SeqCst) > 0
+    }
+
+    /// 通知所有阻塞线程解除阻塞
+    fn no_work_notify_all(&self) {
+        if !self.has_work() {
+            *self.empty_trigger.lock()
+                .expect("Unable to notify all joining threads");
+            self.empty_condvar.notify_all();
+        }
+    }
+
+    /// 尝试为新任务占用一个队列位置，队列已满时返回false
+    fn try_reserve_slot(&self) -> bool {
+        let capacity = match self.queue_capacity {
+            Some(capacity) => capacity,
+            None => {
+                self.queued_count.fetch_add(1, Ordering::SeqCst);
+                return true;
+            }
+        };
+        let mut queued_count = self.queued_count.load(Ordering::SeqCst);
+        loop {
+            if queued_count >= capacity {
+                return false;
+            }
+            match self.queued_count.compare_exchange(
+                queued_count, queued_count + 1, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return true,
+                Err(current) => queued_count = current,
+            }
+        }
+    }
+
+    /// 阻塞直到占用一个队列位置，等待期间线程池被关闭则返回错误
+    fn reserve_slot(&self) -> Result<(), ExecuteError> {
+        if self.try_reserve_slot() {
+            return Ok(());
+        }
+        let mut lock = self.space_trigger.lock()
+            .expect("unable to lock space_trigger");
+        while !self.try_reserve_slot() {
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(ExecuteError::Shutdown);
+            }
+            lock = self.space_condvar.wait(lock)
+                .expect("unable to wait for queue space");
+        }
+        Ok(())
+    }
+
+    /// 工作线程取走任务后释放队列位置
+    fn release_slot(&self) {
+        self.release_slots(1);
+    }
+
+    /// 释放多个队列位置
+    fn release_slots(&self, count: usize) {
+        self.queued_count.fetch_sub(count, Ordering::SeqCst);
+        if self.queue_capacity.is_some() {
+            let _lock = self.space_trigger.lock()
+                .expect("unable to lock space_trigger");
+            if count == 1 {
+                self.space_condvar.notify_one();
+            } else {
+                self.space_condvar.notify_all();
+            }
+        }
+    }
+
+    /// 记录新工作线程的句柄，同时丢弃已退出线程的句柄
+    fn track_thread(&self, handle: thread::JoinHandle<()>) {
+        let mut threads = self.threads.lock()
+            .expect("unable to lock threads");
+        threads.retain(|handle| !handle.is_finished());
+        threads.push(handle);
+    }
+
+    /// 停止接收新任务，唤醒所有等待的线程
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.timer.close();
+        self.notify_all_workers();
+        let _lock = self.space_trigger.lock()
+            .expect("unable to lock space_trigger");
+        self.space_condvar.notify_all();  // 阻塞等待队列空位的线程返回错误
+    }
+
+    /// 等待所有工作线程退出，不等待当前线程自身
+    fn join_threads(&self) {
+        let current = thread::current().id();
+        loop {
+            // 执行剩余任务时发生恐慌会重新拉起线程，因此需要反复获取句柄
+            let threads: Vec<_> = self.threads.lock()
+                .expect("unable to lock threads")
+                .drain(..)
+                .filter(|handle| handle.thread().id() != current)
+                .collect();
+            if threads.is_empty() {
+                return;
+            }
+            for handle in threads {
+                let _ = handle.join();  // 因恐慌退出的线程已由Sentinel处理
+            }
+        }
+    }
+
+    /// 存活线程数超过上限时，让当前工作线程退出
+    fn try_retire(&self) -> bool {
+        let mut thread_count = self.thread_count.load(Ordering::SeqCst);
+        loop {
+            if thread_count <= self.max_thread_count.load(Ordering::SeqCst) {
+                return false;
+            }
+            match self.thread_count.compare_exchange(
+                thread_count, thread_count - 1, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return true,
+                Err(current) => thread_count = current,
+            }
+        }
+    }
+
+    /// 登记新的工作线程，同时创建其本地队列
+    fn register_worker(&self) -> (Arc<Worker>, Deque<Thunk<'static>>) {
+        let local = Deque::new_fifo();
+        let worker = Arc::new(Worker {
+            index: self.next_worker_index.fetch_add(1, Ordering::SeqCst),
+            jobs_completed: AtomicUsize::new(0),
+            stealer: local.stealer(),
+        });
+        self.workers.write()
+            .expect("unable to lock workers")
+            .push(worker.clone());
+        (worker, local)
+    }
+
+    /// 工作线程退出时注销，本地队列中剩余的任务转移到全局队列
+    fn unregister_worker(&self, worker: &Worker, local: Deque<Thunk<'static>>) {
+        self.workers.write()
+            .expect("unable to lock workers")
+            .retain(|w| w.index != worker.index);
+        let mut moved = false;
+        while let Some(job) = local.pop() {
+            self.injectors[Priority::Normal as usize].push(job);
+            moved = true;
+        }
+        if moved {
+            self.notify_all_workers();
+        }
+    }
+
+    /// 唤醒一个休眠的工作线程处理新任务
+    fn notify_one_worker(&self) {
+        if self.sleeping_count.load(Ordering::SeqCst) > 0 {
+            let _lock = self.sleep_trigger.lock()
+                .expect("unable to lock sleep_trigger");
+            self.sleep_condvar.notify_one();
+        }
+    }
+
+    /// 唤醒所有休眠的工作线程，使其检查是否需要退出
+    fn notify_all_workers(&self) {
+        let _lock = self.sleep_trigger.lock()
+            .expect("unable to lock sleep_trigger");
+        self.sleep_condvar.notify_all();
+    }
+
+    /// 没有可执行的任务时休眠，直到有新任务、线程池缩容或被释放
+    fn sleep_until_notified(&self) {
+        let lock = self.sleep_trigger.lock()
+            .expect("unable to lock sleep_trigger");
+        // 先登记休眠再检查队列，与提交任务时先入队再检查休眠数相对应，避免丢失唤醒
+        self.sleeping_count.fetch_add(1, Ordering::SeqCst);
+        if self.queued_count.load(Ordering::SeqCst) == 0
+            && !self.closed.load(Ordering::SeqCst)
+            && self.thread_count.load(Ordering::SeqCst) <= self.max_thread_count.load(Ordering::SeqCst)
+        {
+            let _lock = self.sleep_condvar.wait(lock)
+                .expect("unable to wait for jobs");
+        }
+        self.sleeping_count.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// 将已占用队列位置的任务放入队列
+    ///
+    /// 在本线程池的工作线程中提交的普通优先级任务放入该线程的本地队列，其余任务放入对应优先级的全局队列
+    fn send_job(self: &Arc<Self>, job: Thunk<'static>, priority: Priority) {
+        let job = match priority {
+            Priority::Normal => queue::push_local(self, job).err(),
+            _ => Some(job),
+        };
+        if let Some(job) = job {
+            self.injectors[priority as usize].push(job);
+        }
+        self.notify_one_worker();
+    }
+
+    /// 提交线程池内部产生的任务，如到期的定时任务，不受队列容量限制，线程池关闭后直接丢弃
+    fn push_job(self: &Arc<Self>, job: Thunk<'static>, priority: Priority) {
+        if self.closed.load(Ordering::SeqCst) {
+            return;
+        }
+        self.queued_count.fetch_add(1, Ordering::SeqCst);
+        self.send_job(job, priority);
+    }
+
+    /// 记录工作线程中任务的恐慌，并调用配置的处理函数
+    fn report_panic(&self, payload: &(dyn Any + Send)) {
+        self.record_panic(panic_info::panic_info(payload));
+    }
+
+    fn record_panic(&self, info: PanicInfo) {
+        self.panics.lock()
+            .expect("unable to lock panics")
+            .push(info.clone());
+        if let Some(ref handler) = self.panic_handler {
+            handler(info);
+        }
+    }
+
+    /// 在工作线程中调用启动或退出回调，回调恐慌时以回调名称为标签记录恐慌并返回false
+    fn run_thread_hook(&self, name: &str, hook: &Option<ThreadHook>, index: usize) -> bool {
+        let hook = match *hook {
+            Some(ref hook) => hook,
+            None => return true,
+        };
+        match panic::catch_unwind(AssertUnwindSafe(|| hook(index))) {
+            Ok(()) => true,
+            Err(payload) => {
+                self.panic_count.fetch_add(1, Ordering::SeqCst);
+                let mut info = panic_info::panic_info(payload.as_ref());
+                info.label = Some(name.to_owned());
+                self.record_panic(info);
+                false
+            }
+        }
+    }
+
+    /// 存活线程数不足上限时，补充新的工作线程
+    fn spawn_missing(shared_data: &Arc<ThreadPoolSharedData>) {
+        loop {
+            let thread_count = shared_data.thread_count.load(Ordering::SeqCst);
+            if thread_count >= shared_data.max_thread_count.load(Ordering::SeqCst) {
+                return;
+            }
+            if shared_data.thread_count.compare_exchange(
+                thread_count, thread_count + 1, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+            {
+                let (worker, local) = shared_data.register_worker();
+                spawn_in_pool(shared_data.clone(), worker, local);
+            }
+        }
+    }
+}
+
+/// 线程池，每个工作线程拥有本地队列，空闲时从全局队列或其他线程的本地队列窃取任务
+pub struct ThreadPool {
+    shared_data: Arc<ThreadPoolSharedData>,  // 记录工作线程共享的数据
+}
+
+impl ThreadPool {
+    /// 初始化线程池
+    pub fn new(num_threads: usize) -> ThreadPool {
+        Builder::new().num_threads(num_threads).build()
+    }
+    /// 添加任务到队列，队列已满时按照`QueueFullPolicy`处理
+    pub fn execute<F>(&self, job: F) -> Result<(), ExecuteError>
+        where F: FnOnce() + Send + 'static
+    {
+        self.execute_thunk(Box::new(job), Priority::Normal)
+    }
+    /// 添加带标签的任务到队列，任务恐慌时标签会出现在PanicInfo中
+    pub fn execute_with_label<S, F>(&self, label: S, job: F) -> Result<(), ExecuteError>
+        where S: Into<String>,
+              F: FnOnce() + Send + 'static
+    {
+        self.execute(panic_info::labeled(label.into(), job))
+    }
+    /// 以指定优先级添加任务到队列
+    pub fn execute_with_priority<F>(&self, priority: Priority, job: F) -> Result<(), ExecuteError>
+        where F: FnOnce() + Send + 'static
+    {
+        self.execute_thunk(Box::new(job), priority)
+    }
+    /// 延迟指定时间后执行任务
+    ///
+    /// 任务到期后才进入队列，不受队列容量限制；`join`不会等待尚未到期的任务，线程池关闭时它们被丢弃
+    pub fn execute_after<F>(&self, delay: Duration, job: F) -> Result<(), ExecuteError>
+        where F: FnOnce() + Send + 'static
+    {
+        if self.is_shutdown() {
+            return Err(ExecuteError::Shutdown);
+        }
+        Timer::schedule_once(&self.shared_data, delay, Box::new(job));
+        Ok(())
+    }
+    /// 每隔指定时间执行一次任务，返回用于取消的句柄
+    ///
+    /// 下一次执行在上一次执行结束后间隔`interval`开始，因此同一任务不会并发执行
+    pub fn execute_every<F>(&self, interval: Duration, job: F) -> Result<PeriodicHandle, ExecuteError>
+        where F: Fn() + Send + Sync + 'static
+    {
+        if self.is_shutdown() {
+            return Err(ExecuteError::Shutdown);
+        }
+        Ok(Timer::schedule_periodic(&self.shared_data, interval, job))
+    }
+    /// 尝试添加任务到队列，队列已满时不阻塞，直接将任务返回
+    pub fn try_execute<F>(&self, job: F) -> Result<(), TryExecuteError<F>>
+        where F: FnOnce() + Send + 'static
+    {
+        if self.is_shutdown() {
+            return Err(TryExecuteError::Shutdown(job));
+        }
+        if !self.shared_data.try_reserve_slot() {
+            return Err(TryExecuteError::Full(job));
+        }
+        self.shared_data.send_job(Box::new(job), Priority::Normal);
+        Ok(())
+    }
+    fn execute_thunk(&self, job: Thunk<'static>, priority: Priority) -> Result<(), ExecuteError> {
+        if self.is_shutdown() {
+            return Err(ExecuteError::Shutdown);
+        }
+        if queue::in_pool(&self.shared_data) {
+            // 工作线程等待自身所在线程池的队列空位可能导致死锁，因此不受容量限制
+            self.shared_data.queued_count.fetch_add(1, Ordering::SeqCst);
+            self.shared_data.send_job(job, priority);
+            return Ok(());
+        }
+        match self.shared_data.queue_full_policy {
+            QueueFullPolicy::Block => self.shared_data.reserve_slot()?,
+            QueueFullPolicy::Reject => {
+                if !self.shared_data.try_reserve_slot() {
+                    return Err(ExecuteError::QueueFull);
+                }
+            }
+            QueueFullPolicy::CallerRuns => {
+                if !self.shared_data.try_reserve_slot() {
+                    job.call_box();
+                    return Ok(());
+                }
+            }
+        }
+        self.shared_data.send_job(job, priority);
+        Ok(())
+    }
+    /// 在需要时阻塞主线程等待线程池中所有任务执行完毕
+    pub fn join(&self) {
+        let _ = self.join_and_check();
+    }
+    /// 等待所有任务执行完毕，上次join以来有任务在工作线程中恐慌时返回这些恐慌的信息
+    ///
+    /// 通过`spawn`和`scoped`提交的任务的恐慌会交给调用方，不在此列
+    pub fn join_and_check(&self) -> Result<(), Vec<PanicInfo>> {
+        if self.shared_data.has_work() {  // 线程池闲置则无需等待
+            let mut lock = self.shared_data.empty_trigger.lock().unwrap();
+            while self.shared_data.has_work() {  // 若线程池中工作线程正在执行，则调用条件变量阻塞当前线程等待
+                lock = self.shared_data
+                    .empty_condvar.wait(lock).unwrap();
+            }
+        }
+        let panics = std::mem::take(&mut *self.shared_data.panics.lock()
+            .expect("unable to lock panics"));
+        if panics.is_empty() {
+            Ok(())
+        } else {
+            Err(panics)
+        }
+    }
+    /// 关闭线程池：不再接收新任务，执行完队列中剩余的任务后等待所有工作线程退出
+    ///
+    /// 在本线程池的工作线程中调用时不会等待当前线程
+    pub fn shutdown(&self) {
+        self.shared_data.close();
+        self.shared_data.join_threads();
+    }
+    /// 立即关闭线程池：不再接收新任务，丢弃队列中尚未执行的任务，等待正在执行的任务结束
+    ///
+    /// 返回被丢弃的任务数，被丢弃的`spawn`任务结果为`TaskError::Cancelled`
+    pub fn shutdown_now(&self) -> usize {
+        self.shared_data.close();
+        let dropped = queue::drain(&self.shared_data);
+        self.shared_data.release_slots(dropped);
+        self.shared_data.no_work_notify_all();
+        self.shared_data.notify_all_workers();
+        self.shared_data.join_threads();
+        dropped
+    }
+    /// 线程池是否已关闭
+    pub fn is_shutdown(&self) -> bool {
+        self.shared_data.closed.load(Ordering::SeqCst)
+    }
+    /// 调整线程池的最大线程数，扩容时立即创建新线程，缩容时空闲线程将逐个退出
+    ///
+    /// 正在执行任务的线程会在任务完成后再退出，因此可以在任务执行期间安全调用
+    pub fn set_num_threads(&self, num_threads: usize) {
+        assert!(num_threads > 0);
+        let prev_num_threads = self.shared_data
+            .max_thread_count.swap(num_threads, Ordering::SeqCst);
+        if num_threads > prev_num_threads {
+            ThreadPoolSharedData::spawn_missing(&self.shared_data);
+        } else {
+            self.shared_data.notify_all_workers();  // 唤醒休眠的空闲线程
+        }
+    }
+    /// 线程池当前允许的最大线程数
+    pub fn max_count(&self) -> usize {
+        self.shared_data.max_thread_count.load(Ordering::Relaxed)
+    }
+    /// 队列中等待执行的任务数
+    pub fn queued_count(&self) -> usize {
+        self.shared_data.queued_count.load(Ordering::Relaxed)
+    }
+    /// 正在执行任务的工作线程数
+    pub fn active_count(&self) -> usize {
+        self.shared_data.active_count.load(Ordering::SeqCst)
+    }
+    /// 执行任务时发生恐慌的次数
+    pub fn panic_count(&self) -> usize {
+        self.shared_data.panic_count.load(Ordering::Relaxed)
+    }
+    /// 获取线程池当前状态的快照
+    pub fn stats(&self) -> ThreadPoolStats {
+        let mut workers: Vec<WorkerStats> = self.shared_data.workers.read()
+            .expect("unable to lock workers")
+            .iter()
+            .map(|worker| WorkerStats {
+                index: worker.index,
+                jobs_completed: worker.jobs_completed.load(Ordering::Relaxed),
+            })
+            .collect();
+        workers.sort_by_key(|worker| worker.index);
+        ThreadPoolStats {
+            queued_count: self.queued_count(),
+            active_count: self.active_count(),
+            panic_count: self.panic_count(),
+            max_count: self.max_count(),
+            thread_count: self.shared_data.thread_count.load(Ordering::SeqCst),
+            completed_count: self.shared_data.completed_count.load(Ordering::Relaxed),
+            workers,
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    /// 释放线程池后，工作线程执行完队列中剩余的任务便会退出
+    fn drop(&mut self) {
+        self.shared_data.close();
+    }
+}
+
+/// 线程池构造器，用于配置工作线程数、线程名称和栈大小
+#[derive(Clone, Default)]
+pub struct Builder {
+    num_threads: Option<usize>,  // 工作线程数
+    thread_name: Option<String>,  // 线程名称
+    thread_stack_size: Option<usize>,  // 线程栈大小
+    numbered_names: bool,  // 线程名称是否带编号
+    queue_capacity: Option<usize>,  // 队列容量
+    queue_full_policy: QueueFullPolicy,  // 队列已满时的处理策略
+    panic_handler: Option<PanicHandler>,  // 任务恐慌时的处理函数
+    on_thread_start: Option<ThreadHook>,  // 工作线程启动时的回调
+    on_thread_stop: Option<ThreadHook>,  // 工作线程退出时的回调
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder {
+            num_threads: None,
+            thread_name: None,
+            thread_stack_size: None,
+            numbered_names: false,
+            queue_capacity: None,
+            queue_full_policy: QueueFullPolicy::default(),
+            panic_handler: None,
+            on_thread_start: None,
+            on_thread_stop: None,
+        }
+    }
+    // 配置工作线程数
+    pub fn num_threads(mut self, num_threads: usize) -> Builder {
+        assert!(num_threads > 0);
+        self.num_threads = Some(num_threads);
+        self
+    }
+    // 配置工作线程名称
+    pub fn thread_name(mut self, name: String) -> Builder {
+        self.thread_name = Some(name);
+        self
+    }
+    // 配置工作线程栈大小，单位为字节
+    pub fn thread_stack_size(mut self, size: usize) -> Builder {
+        self.thread_stack_size = Some(size);
+        self
+    }
+    // 为工作线程名称追加编号，如`name-0`、`name-1`，需同时配置thread_name
+    pub fn numbered_thread_names(mut self, numbered: bool) -> Builder {
+        self.numbered_names = numbered;
+        self
+    }
+    // 配置任务队列容量，默认无界
+    pub fn queue_capacity(mut self, capacity: usize) -> Builder {
+        assert!(capacity > 0);
+        self.queue_capacity = Some(capacity);
+        self
+    }
+    // 配置队列已满时的处理策略，默认阻塞
+    pub fn queue_full_policy(mut self, policy: QueueFullPolicy) -> Builder {
+        self.queue_full_policy = policy;
+        self
+    }
+    // 配置任务在工作线程中恐慌时的处理函数，在发生恐慌的工作线程中调用
+    pub fn panic_handler<F>(mut self, handler: F) -> Builder
+        where F: Fn(PanicInfo) + Send + Sync + 'static
+    {
+        self.panic_handler = Some(Arc::new(handler));
+        self
+    }
+    // 配置工作线程启动时在该线程中执行的回调，参数为工作线程编号，可用于初始化线程局部资源
+    // 工作线程因任务恐慌被重新拉起时会再次调用；回调恐慌时记录恐慌，该线程退出且不再重新拉起
+    pub fn on_thread_start<F>(mut self, hook: F) -> Builder
+        where F: Fn(usize) + Send + Sync + 'static
+    {
+        self.on_thread_start = Some(Arc::new(hook));
+        self
+    }
+    // 配置工作线程退出时在该线程中执行的回调，参数为工作线程编号，因任务恐慌退出时也会调用；回调恐慌时记录恐慌
+    pub fn on_thread_stop<F>(mut self, hook: F) -> Builder
+        where F: Fn(usize) + Send + Sync + 'static
+    {
+        self.on_thread_stop = Some(Arc::new(hook));
+        self
+    }
+    // 初始化最终线程池
+    pub fn build(self) -> ThreadPool {
+        let num_threads = self.num_threads
+            .unwrap_or_else(num_cpus::get);
+        let shared_data = Arc::new(ThreadPoolSharedData {
+            name: self.thread_name,
+            numbered_names: self.numbered_names,
+            injectors: [Injector::new(), Injector::new(), Injector::new()],
+            sleep_trigger: Mutex::new(()),
+            sleep_condvar: Condvar::new(),
+            sleeping_count: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            threads: Mutex::new(Vec::new()),
+            timer: Timer::new(),
+            empty_condvar: Condvar::new(),
+            empty_trigger: Mutex::new(()),
+            queue_capacity: self.queue_capacity,
+            queue_full_policy: self.queue_full_policy,
+            space_trigger: Mutex::new(()),
+            space_condvar: Condvar::new(),
+            queued_count: AtomicUsize::new(0),
+            active_count: AtomicUsize::new(0),
+            max_thread_count: AtomicUsize::new(num_threads),
+            thread_count: AtomicUsize::new(0),
+            panic_count: AtomicUsize::new(0),
+            completed_count: AtomicUsize::new(0),
+            panic_handler: self.panic_handler,
+            panics: Mutex::new(Vec::new()),
+            on_thread_start: self.on_thread_start,
+            on_thread_stop: self.on_thread_stop,
+            workers: RwLock::new(Vec::new()),
+            next_worker_index: AtomicUsize::new(0),
+            stack_size: self.thread_stack_size,
+        }); 
+        ThreadPoolSharedData::spawn_missing(&shared_data);
+        ThreadPool { shared_data }
+    }
+}
+
+fn spawn_in_pool(shared_data: Arc<ThreadPoolSharedData>, worker: Arc<Worker>,
+                 local: Deque<Thunk<'static>>) {
+    let mut builder = thread::Builder::new();
+    if let Some(ref name) = shared_data.name {
+        if shared_data.numbered_names {
+            builder = builder.name(format!("{}-{}", name, worker.index));
+        } else {
+            builder = builder.name(name.clone());
+        }
+    }
+
+    if let Some(ref stack_size) = shared_data.stack_size {
+        builder = builder.stack_size(stack_size.to_owned());
+    }
+
+    let threads = shared_data.clone();
+    let handle = builder.spawn(move || {
+        queue::enter(&shared_data, local);  // 本地队列放入线程局部存储，供提交任务时使用
+        // 启动回调先于Sentinel执行：回调恐慌时线程直接退出且不再重新拉起，避免回调持续失败时无限创建线程
+        if !shared_data.run_thread_hook("on_thread_start", &shared_data.on_thread_start, worker.index) {
+            shared_data.thread_count.fetch_sub(1, Ordering::SeqCst);
+            shared_data.unregister_worker(&worker, queue::leave());
+            shared_data.no_work_notify_all();
+            return;
+        }
+        let mut sentinel = Sentinel::new(&shared_data, &worker);  // 对具体线程进行监控
+        let mut idle_rounds = 0;
+        loop {  // 从本地队列、全局队列或其他线程获取任务
+            if shared_data.try_retire() {  // 线程池已缩容，当前线程退出
+                break;
+            }
+            let job = match queue::find_job(&shared_data, &worker) {
+                Some(job) => {
+                    idle_rounds = 0;
+                    job
+                }
+                None => {
+                    if shared_data.closed.load(Ordering::SeqCst)
+                        && shared_data.queued_count.load(Ordering::SeqCst) == 0
+                    {  // 线程池已关闭且任务已全部取走
+                        shared_data.thread_count.fetch_sub(1, Ordering::SeqCst);
+                        break;
+                    }
+                    if idle_rounds < IDLE_SPIN_ROUNDS {  // 休眠前先让出CPU重试几次，减少唤醒的开销
+                        idle_rounds += 1;
+                        thread::yield_now();
+                    } else {
+                        idle_rounds = 0;
+                        shared_data.sleep_until_notified();
+                    }
+                    continue;
+                }
+            };
+            shared_data.active_count.fetch_add(1, Ordering::SeqCst);  // 工作线程执行
+            sentinel.running = true;
+            shared_data.release_slot();  // 获取到任务
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| job.call_box())) {
+                shared_data.report_panic(payload.as_ref());
+                panic::resume_unwind(payload);  // 由Sentinel统计恐慌并重新拉起线程
+            }
+            worker.jobs_completed.fetch_add(1, Ordering::Relaxed);
+            shared_data.completed_count.fetch_add(1, Ordering::Relaxed);
+            sentinel.running = false;
+            shared_data.active_count.fetch_sub(1, Ordering::SeqCst);  // 工作线程空闲
+            shared_data.no_work_notify_all();  // 通知阻塞线程恢复
+        }
+        sentinel.cancel();  // 设置实力状态，表示该线程正常执行完所有任务；退出回调恐慌也不再重新拉起
+        shared_data.run_thread_hook("on_thread_stop", &shared_data.on_thread_stop, worker.index);
+        shared_data.unregister_worker(&worker, queue::leave());
+    }).unwrap();
+    threads.track_thread(handle);
+}
+
+struct Sentinel<'a> {
+    shared_data: &'a Arc<ThreadPoolSharedData>,
+    worker: &'a Arc<Worker>,
+    active: bool,
+    running: bool,  // 是否正在执行任务，只有此时才计入active_count
+}
+
+impl<'a> Sentinel<'a> {
+    fn new(shared_data: &'a Arc<ThreadPoolSharedData>, worker: &'a Arc<Worker>) -> Sentinel<'a> {
+        Sentinel {
+            shared_data,
+            worker,
+            active: true,
+            running: false,
+        }
+    }
+    fn cancel(mut self) {
+        self.active = false;
+    }
+}
+impl<'a> Drop for Sentinel<'a> {
+    /// 处理非正常状态的工作线程
+    fn drop(&mut self) {
+        if self.active {
+            self.shared_data.run_thread_hook("on_thread_stop", &self.shared_data.on_thread_stop,
+                                             self.worker.index);
+            if thread::panicking() {  // 先于active_count更新，保证join返回后能看到恐慌计数
+                self.shared_data.panic_count.fetch_add(1, Ordering::SeqCst);
+            }
+            if self.running {
+                self.shared_data.active_count.fetch_sub(1, Ordering::SeqCst);
+            }
+            self.shared_data.no_work_notify_all();
+            let local = queue::leave();  // 本地队列交给新线程，其中的任务不会丢失
+            if self.shared_data.try_retire() {  // 线程池缩容时不再重新拉起
+                self.shared_data.unregister_worker(self.worker, local);
+            } else {
+                spawn_in_pool(self.shared_data.clone(), self.worker.clone(), local)
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+
+use super::task::panic_message;
+
+/// 工作线程中任务恐慌的信息，传给`Builder::panic_handler`配置的处理函数，也由`ThreadPool::join_and_check`返回
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicInfo {
+    pub label: Option<String>,  // 提交任务时指定的标签
+    pub message: Option<String>,  // 恐慌信息，payload不是字符串时为None
+    pub thread_name: Option<String>,  // 发生恐慌的工作线程名称
+}
+
+impl fmt::Display for PanicInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.label {
+            Some(ref label) => write!(f, "job '{}' panicked", label)?,
+            None => write!(f, "job panicked")?,
+        }
+        if let Some(ref thread_name) = self.thread_name {
+            write!(f, " in thread '{}'", thread_name)?;
+        }
+        if let Some(ref message) = self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) type PanicHandler = Arc<dyn Fn(PanicInfo) + Send + Sync>;
+
+thread_local! {
+    static PANICKED_LABEL: RefCell<Option<String>> = const { RefCell::new(None) };  // 刚刚恐慌的任务的标签
+}
+
+/// 为任务附加标签，任务恐慌时记下标签再继续展开，由工作线程生成PanicInfo时取走
+pub(crate) fn labeled<F>(label: String, job: F) -> impl FnOnce() + Send
+    where F: FnOnce() + Send
+{
+    move || {
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+            PANICKED_LABEL.with(|panicked| *panicked.borrow_mut() = Some(label));
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+/// 根据恐慌的payload和当前线程生成PanicInfo
+pub(crate) fn panic_info(payload: &(dyn Any + Send)) -> PanicInfo {
+    PanicInfo {
+        label: PANICKED_LABEL.with(|panicked| panicked.borrow_mut().take()),
+        message: panic_message(payload).map(str::to_owned),
+        thread_name: thread::current().name().map(str::to_owned),
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Mutex;
+
+use super::ThreadPool;
+
+/// 每个工作线程平均分到的块数，多分几块可以让先做完的线程继续窃取剩余的块
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// 一块输入及其结果，任务未能提交时由调用线程处理剩余的输入
+struct Chunk<T, R> {
+    input: Option<Vec<T>>,  // 尚未处理的输入，任务开始时取走
+    output: Option<R>,  // 该块的处理结果
+}
+
+impl ThreadPool {
+    /// 并行地对每个元素调用`f`，结果的顺序与输入一致
+    ///
+    /// 与`scoped`相同，不要在本线程池的工作线程中调用；`f`恐慌时恐慌会在调用线程中重新抛出
+    pub fn par_map<I, F, R>(&self, iter: I, f: F) -> Vec<R>
+        where I: IntoIterator,
+              I::Item: Send,
+              F: Fn(I::Item) -> R + Sync,
+              R: Send
+    {
+        self.par_chunks(iter, |chunk| chunk.into_iter().map(&f).collect::<Vec<_>>())
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// 并行地对每个元素调用`f`
+    pub fn par_for_each<I, F>(&self, iter: I, f: F)
+        where I: IntoIterator,
+              I::Item: Send,
+              F: Fn(I::Item) + Sync
+    {
+        self.par_chunks(iter, |chunk| chunk.into_iter().for_each(&f));
+    }
+
+    /// 并行地用`op`归约所有元素，`identity`提供每一块的初始值，输入为空时返回`identity()`
+    ///
+    /// 各块按输入顺序合并，因此`op`只需满足结合律，结果与顺序执行相同
+    pub fn par_reduce<I, ID, F>(&self, iter: I, identity: ID, op: F) -> I::Item
+        where I: IntoIterator,
+              I::Item: Send,
+              ID: Fn() -> I::Item + Sync,
+              F: Fn(I::Item, I::Item) -> I::Item + Sync
+    {
+        self.par_chunks(iter, |chunk| chunk.into_iter().fold(identity(), &op))
+            .into_iter()
+            .fold(identity(), &op)
+    }
+
+    /// 将输入分块后在线程池中处理，按输入顺序返回每一块的结果
+    fn par_chunks<I, F, R>(&self, iter: I, f: F) -> Vec<R>
+        where I: IntoIterator,
+              I::Item: Send,
+              F: Fn(Vec<I::Item>) -> R + Sync,
+              R: Send
+    {
+        let items: Vec<I::Item> = iter.into_iter().collect();
+        let num_chunks = self.max_count().max(1) * CHUNKS_PER_THREAD;
+        let chunk_size = items.len().div_ceil(num_chunks).max(1);
+        let mut items = items.into_iter();
+        let mut chunks = Vec::new();
+        loop {
+            let input: Vec<_> = items.by_ref().take(chunk_size).collect();
+            if input.is_empty() {
+                break;
+            }
+            chunks.push(Mutex::new(Chunk { input: Some(input), output: None }));
+        }
+        let f = &f;
+        self.scoped(|scope| {
+            for chunk in chunks.iter() {
+                // 任务被拒绝或线程池已关闭时输入仍留在块中，稍后由调用线程处理，因此忽略错误
+                let _ = scope.execute(move || {
+                    let input = chunk.lock()
+                        .expect("unable to lock chunk")
+                        .input.take();
+                    if let Some(input) = input {
+                        let output = f(input);
+                        chunk.lock()
+                            .expect("unable to lock chunk")
+                            .output = Some(output);
+                    }
+                });
+            }
+        });
+        chunks.into_iter()
+            .map(|chunk| {
+                let chunk = chunk.into_inner()
+                    .expect("unable to lock chunk");
+                match chunk.input {
+                    Some(input) => f(input),
+                    None => chunk.output.expect("chunk finished without output"),
+                }
+            })
+            .collect()
+    }
+}
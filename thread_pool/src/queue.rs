@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crossbeam_deque::{Steal, Worker as Deque};
+
+use super::{Priority, ThreadPoolSharedData, Thunk, Worker};
+
+/// 工作线程的本地队列
+struct LocalQueue {
+    pool: usize,  // 所属线程池共享数据的地址，用于区分不同线程池
+    deque: Deque<Thunk<'static>>,
+}
+
+thread_local! {
+    static LOCAL: RefCell<Option<LocalQueue>> = const { RefCell::new(None) };
+}
+
+fn pool_id(shared_data: &Arc<ThreadPoolSharedData>) -> usize {
+    Arc::as_ptr(shared_data) as usize
+}
+
+/// 工作线程启动时登记本地队列
+pub(crate) fn enter(shared_data: &Arc<ThreadPoolSharedData>, deque: Deque<Thunk<'static>>) {
+    LOCAL.with(|local| {
+        *local.borrow_mut() = Some(LocalQueue {
+            pool: pool_id(shared_data),
+            deque,
+        });
+    });
+}
+
+/// 工作线程退出时取回本地队列
+pub(crate) fn leave() -> Deque<Thunk<'static>> {
+    LOCAL.with(|local| local.borrow_mut().take())
+        .expect("worker thread has no local queue")
+        .deque
+}
+
+/// 当前线程是否为该线程池的工作线程
+pub(crate) fn in_pool(shared_data: &Arc<ThreadPoolSharedData>) -> bool {
+    LOCAL.with(|local| match *local.borrow() {
+        Some(ref queue) => queue.pool == pool_id(shared_data),
+        None => false,
+    })
+}
+
+/// 当前线程为该线程池的工作线程时，将任务放入本地队列，否则将任务返回
+pub(crate) fn push_local(shared_data: &Arc<ThreadPoolSharedData>, job: Thunk<'static>)
+    -> Result<(), Thunk<'static>>
+{
+    LOCAL.with(|local| match *local.borrow() {
+        Some(ref queue) if queue.pool == pool_id(shared_data) => {
+            queue.deque.push(job);
+            Ok(())
+        }
+        _ => Err(job),
+    })
+}
+
+/// 依次从高优先级全局队列、本地队列、普通优先级全局队列、其他工作线程的本地队列
+/// 和低优先级全局队列中获取任务
+pub(crate) fn find_job(shared_data: &ThreadPoolSharedData, worker: &Worker) -> Option<Thunk<'static>> {
+    let injector = |priority: Priority| &shared_data.injectors[priority as usize];
+    LOCAL.with(|local| {
+        let local = local.borrow();
+        let deque = &local.as_ref()
+            .expect("worker thread has no local queue")
+            .deque;
+        steal(|| injector(Priority::High).steal())
+            .or_else(|| deque.pop())
+            .or_else(|| steal(|| injector(Priority::Normal).steal_batch_and_pop(deque)))
+            .or_else(|| {
+                let workers = shared_data.workers.read()
+                    .expect("unable to lock workers");
+                // 从自身之后的线程开始窃取，避免所有线程都争抢同一个队列
+                let start = workers.iter()
+                    .position(|w| w.index == worker.index)
+                    .map_or(0, |position| position + 1);
+                steal(|| {
+                    workers.iter()
+                        .cycle()
+                        .skip(start)
+                        .take(workers.len())
+                        .filter(|w| w.index != worker.index)
+                        .map(|w| w.stealer.steal_batch_and_pop(deque))
+                        .collect()
+                })
+            })
+            .or_else(|| steal(|| injector(Priority::Low).steal()))
+    })
+}
+
+/// 取出并丢弃全局队列和所有本地队列中的任务，返回丢弃的任务数
+pub(crate) fn drain(shared_data: &ThreadPoolSharedData) -> usize {
+    let mut dropped = 0;
+    for injector in shared_data.injectors.iter() {
+        while steal(|| injector.steal()).is_some() {
+            dropped += 1;
+        }
+    }
+    let workers = shared_data.workers.read()
+        .expect("unable to lock workers");
+    for worker in workers.iter() {
+        while steal(|| worker.stealer.steal()).is_some() {
+            dropped += 1;
+        }
+    }
+    dropped
+}
+
+/// 重复尝试窃取，直到成功或确认队列为空
+fn steal<F>(mut attempt: F) -> Option<Thunk<'static>>
+    where F: FnMut() -> Steal<Thunk<'static>>
+{
+    loop {
+        match attempt() {
+            Steal::Success(job) => return Some(job),
+            Steal::Empty => return None,
+            Steal::Retry => continue,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::any::Any;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Condvar, Mutex};
+
+use super::{ExecuteError, Priority, ThreadPool, ThreadPoolSharedData, Thunk};
+
+/// 作用域内所有任务共享的状态
+struct ScopeState {
+    pending: Mutex<usize>,  // 尚未结束的任务数
+    done: Condvar,  // 任务全部结束时通知等待的线程
+    panic: Mutex<Option<Box<dyn Any + Send>>>,  // 记录第一个发生恐慌的任务的payload
+}
+
+impl ScopeState {
+    fn job_finished(&self) {
+        let mut pending = self.pending.lock()
+            .expect("unable to lock scope pending count");
+        *pending -= 1;
+        if *pending == 0 {
+            self.done.notify_all();
+        }
+    }
+}
+
+/// 任务结束时（无论正常执行、恐慌还是未执行就被丢弃）减少计数
+struct PendingGuard(Arc<ScopeState>);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.0.job_finished();
+    }
+}
+
+/// 作用域任务及其计数，字段按声明顺序析构
+///
+/// 任务未执行就被丢弃时（如`shutdown_now`清空队列），`job`及其借用的数据先于`guard`释放，
+/// 保证`scoped`返回时任务的析构已经结束
+struct ScopedJob<F> {
+    job: F,
+    guard: PendingGuard,
+}
+
+impl<F: FnOnce()> ScopedJob<F> {
+    fn run(self, shared_data: &ThreadPoolSharedData) {
+        let ScopedJob { job, guard } = self;
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(job)) {
+            shared_data.panic_count.fetch_add(1, Ordering::SeqCst);
+            let mut panic = guard.0.panic.lock()
+                .expect("unable to lock scope panic");
+            if panic.is_none() {
+                *panic = Some(payload);
+            }
+        }
+    }
+}
+
+/// 作用域任务的句柄，由`ThreadPool::scoped`创建
+///
+/// 通过它提交的任务可以借用生命周期至少为`'scope`的数据
+pub struct Scope<'pool, 'scope> {
+    pool: &'pool ThreadPool,
+    state: Arc<ScopeState>,
+    _marker: PhantomData<::std::cell::Cell<&'scope mut ()>>,  // 保证'scope不变
+}
+
+impl<'pool, 'scope> Scope<'pool, 'scope> {
+    /// 在线程池中执行可借用调用方栈上数据的任务，队列已满时按照线程池的策略处理
+    pub fn execute<F>(&self, job: F) -> Result<(), ExecuteError>
+        where F: FnOnce() + Send + 'scope
+    {
+        *self.state.pending.lock()
+            .expect("unable to lock scope pending count") += 1;
+        let scoped_job = ScopedJob {
+            job,
+            guard: PendingGuard(self.state.clone()),
+        };
+        let shared_data = self.pool.shared_data.clone();
+        let thunk: Thunk<'scope> = Box::new(move || scoped_job.run(&shared_data));
+        // 安全性：`ThreadPool::scoped`返回前会等待所有任务结束，任务借用的数据不会失效
+        let thunk = unsafe { mem::transmute::<Thunk<'scope>, Thunk<'static>>(thunk) };
+        self.pool.execute_thunk(thunk, Priority::Normal)
+    }
+
+    /// 阻塞直到作用域内的任务全部结束
+    fn join_all(&self) {
+        let mut pending = self.state.pending.lock()
+            .expect("unable to lock scope pending count");
+        while *pending > 0 {
+            pending = self.state.done.wait(pending)
+                .expect("unable to wait for scoped jobs");
+        }
+    }
+}
+
+impl ThreadPool {
+    /// 创建作用域，其中提交的任务可以借用非`'static`数据
+    ///
+    /// 返回前会阻塞直到作用域内所有任务结束；若有任务发生恐慌，恐慌会在调用线程中重新抛出。
+    /// 不要在本线程池的工作线程中调用，否则可能因等待自身而死锁
+    pub fn scoped<'pool, 'scope, F, R>(&'pool self, f: F) -> R
+        where F: FnOnce(&Scope<'pool, 'scope>) -> R
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState {
+                pending: Mutex::new(0),
+                done: Condvar::new(),
+                panic: Mutex::new(None),
+            }),
+            _marker: PhantomData,
+        };
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        scope.join_all();  // 即使f发生恐慌也必须等待任务结束，避免借用的数据失效
+        let result = match result {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        };
+        let payload = scope.state.panic.lock()
+            .expect("unable to lock scope panic")
+            .take();
+        if let Some(payload) = payload {
+            panic::resume_unwind(payload);
+        }
+        result
+    }
+}
@@ -0,0 +1,28 @@
+/// 线程池某一时刻的状态快照
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadPoolStats {
+    pub queued_count: usize,  // 队列中等待执行的任务数
+    pub active_count: usize,  // 正在执行任务的工作线程数
+    pub panic_count: usize,  // 执行任务时发生恐慌的次数
+    pub max_count: usize,  // 线程池允许的最大线程数
+    pub thread_count: usize,  // 当前存活的工作线程数
+    pub completed_count: usize,  // 正常执行完毕的任务总数，包含已退出线程执行的任务
+    pub workers: Vec<WorkerStats>,  // 每个存活工作线程的统计，按编号排序
+}
+
+/// 单个工作线程的统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerStats {
+    pub index: usize,  // 工作线程编号
+    pub jobs_completed: usize,  // 该线程正常执行完毕的任务数
+}
+
+impl ThreadPoolStats {
+    /// 线程池饱和度，即正在执行任务的线程占最大线程数的比例
+    pub fn saturation(&self) -> f64 {
+        if self.max_count == 0 {
+            return 0.0;
+        }
+        self.active_count as f64 / self.max_count as f64
+    }
+}
@@ -0,0 +1,197 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use super::ThreadPool;
+
+/// 任务未能正常返回结果的原因
+pub enum TaskError {
+    Panicked(Box<dyn Any + Send>),  // 任务发生恐慌，携带恐慌的payload
+    Cancelled,  // 任务未被执行就被丢弃，如被拒绝或线程池已关闭
+}
+
+impl TaskError {
+    /// 任务是否因恐慌而结束
+    pub fn is_panic(&self) -> bool {
+        matches!(*self, TaskError::Panicked(_))
+    }
+    /// 任务是否未被执行
+    pub fn is_cancelled(&self) -> bool {
+        matches!(*self, TaskError::Cancelled)
+    }
+    /// 获取恐慌的payload，任务并非因恐慌结束时会引发恐慌
+    pub fn into_panic(self) -> Box<dyn Any + Send> {
+        match self {
+            TaskError::Panicked(payload) => payload,
+            TaskError::Cancelled => panic!("task was cancelled, not panicked"),
+        }
+    }
+}
+
+impl fmt::Debug for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TaskError::Panicked(_) => f.write_str("Panicked(..)"),
+            TaskError::Cancelled => f.write_str("Cancelled"),
+        }
+    }
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TaskError::Panicked(ref payload) => match panic_message(payload.as_ref()) {
+                Some(message) => write!(f, "task panicked: {}", message),
+                None => write!(f, "task panicked"),
+            },
+            TaskError::Cancelled => write!(f, "task was cancelled before it ran"),
+        }
+    }
+}
+
+impl Error for TaskError {}
+
+/// 尝试从恐慌的payload中取出字符串信息
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
+
+/// 在任务与句柄之间共享的状态
+struct TaskState<R> {
+    result: Option<Result<R, TaskError>>,  // 任务结果，取走后为None
+    taken: bool,  // 结果是否已被取走
+    waker: Option<Waker>,  // 以Future方式等待时，用于在任务完成后唤醒
+}
+
+struct TaskShared<R> {
+    state: Mutex<TaskState<R>>,
+    done: Condvar,  // 以阻塞方式等待时，任务完成后通知
+}
+
+impl<R> TaskShared<R> {
+    fn complete(&self, result: Result<R, TaskError>) {
+        let mut state = self.state.lock()
+            .expect("unable to lock task state");
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.done.notify_all();
+    }
+}
+
+/// 随任务一起提交，任务未执行就被丢弃时将结果设置为`Cancelled`
+struct CompletionGuard<R> {
+    shared: Option<Arc<TaskShared<R>>>,
+}
+
+impl<R> CompletionGuard<R> {
+    fn complete(mut self, result: Result<R, TaskError>) {
+        if let Some(shared) = self.shared.take() {
+            shared.complete(result);
+        }
+    }
+}
+
+impl<R> Drop for CompletionGuard<R> {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            shared.complete(Err(TaskError::Cancelled));
+        }
+    }
+}
+
+/// `ThreadPool::spawn`返回的任务句柄，可阻塞等待、轮询或以Future方式获取任务结果
+pub struct TaskHandle<R> {
+    shared: Arc<TaskShared<R>>,
+}
+
+impl<R> TaskHandle<R> {
+    /// 阻塞直到任务结束并返回结果
+    pub fn join(self) -> Result<R, TaskError> {
+        let mut state = self.shared.state.lock()
+            .expect("unable to lock task state");
+        loop {
+            if let Some(result) = take_result(&mut state) {
+                return result;
+            }
+            state = self.shared.done.wait(state)
+                .expect("unable to wait for task");
+        }
+    }
+    /// 不阻塞地获取任务结果，任务尚未结束或结果已被取走时返回None
+    pub fn try_join(&mut self) -> Option<Result<R, TaskError>> {
+        let mut state = self.shared.state.lock()
+            .expect("unable to lock task state");
+        if state.taken {
+            return None;
+        }
+        take_result(&mut state)
+    }
+    /// 任务是否已经结束
+    pub fn is_finished(&self) -> bool {
+        let state = self.shared.state.lock()
+            .expect("unable to lock task state");
+        state.result.is_some() || state.taken
+    }
+}
+
+fn take_result<R>(state: &mut TaskState<R>) -> Option<Result<R, TaskError>> {
+    assert!(!state.taken, "task result has already been taken");
+    let result = state.result.take();
+    state.taken = result.is_some();
+    result
+}
+
+impl<R> Future for TaskHandle<R> {
+    type Output = Result<R, TaskError>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock()
+            .expect("unable to lock task state");
+        match take_result(&mut state) {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl ThreadPool {
+    /// 提交有返回值的任务，通过返回的句柄获取结果
+    ///
+    /// 任务恐慌时结果为`Err(TaskError::Panicked)`，任务被拒绝、被丢弃或线程池已关闭时结果为`Err(TaskError::Cancelled)`
+    pub fn spawn<F, R>(&self, job: F) -> TaskHandle<R>
+        where F: FnOnce() -> R + Send + 'static,
+              R: Send + 'static
+    {
+        let shared = Arc::new(TaskShared {
+            state: Mutex::new(TaskState {
+                result: None,
+                taken: false,
+                waker: None,
+            }),
+            done: Condvar::new(),
+        });
+        let guard = CompletionGuard { shared: Some(shared.clone()) };
+        let shared_data = self.shared_data.clone();
+        // 任务被拒绝时会随闭包一起被丢弃，由CompletionGuard设置结果，因此忽略错误
+        let _ = self.execute(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job))
+                .map_err(|payload| {
+                    shared_data.panic_count.fetch_add(1, Ordering::SeqCst);
+                    TaskError::Panicked(payload)
+                });
+            guard.complete(result);
+        });
+        TaskHandle { shared }
+    }
+}
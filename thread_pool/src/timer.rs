@@ -0,0 +1,201 @@
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Priority, ThreadPoolSharedData, Thunk};
+
+/// 周期任务的句柄，用于取消后续的执行，丢弃句柄不会取消任务
+#[derive(Debug, Clone)]
+pub struct PeriodicHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl PeriodicHandle {
+    /// 取消周期任务，正在执行的那一次不受影响
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+    /// 周期任务是否已被取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 周期执行的任务
+struct PeriodicJob {
+    job: Box<dyn Fn() + Send + Sync>,
+    interval: Duration,
+    cancelled: Arc<AtomicBool>,
+}
+
+enum TimerJob {
+    Once(Thunk<'static>),  // 延迟执行一次
+    Periodic(Arc<PeriodicJob>),  // 周期执行
+}
+
+/// 定时器中的一项，按到期时间排序，时间相同时按登记顺序
+struct TimerEntry {
+    deadline: Instant,
+    seq: u64,
+    job: TimerJob,
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &TimerEntry) -> bool {
+        self.deadline == other.deadline && self.seq == other.seq
+    }
+}
+
+impl Eq for TimerEntry {}
+
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &TimerEntry) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    /// BinaryHeap为大顶堆，反转比较结果使最早到期的一项位于堆顶
+    fn cmp(&self, other: &TimerEntry) -> CmpOrdering {
+        (other.deadline, other.seq).cmp(&(self.deadline, self.seq))
+    }
+}
+
+struct TimerState {
+    entries: BinaryHeap<TimerEntry>,  // 尚未到期的任务
+    next_seq: u64,  // 下一项的登记序号
+    started: bool,  // 定时线程是否已启动
+    closed: bool,  // 线程池关闭后定时线程退出，未到期的任务被丢弃
+}
+
+/// 线程池的定时器，由一个定时线程在任务到期时将其提交到线程池
+pub(crate) struct Timer {
+    state: Mutex<TimerState>,
+    condvar: Condvar,  // 有新的任务登记或线程池关闭时唤醒定时线程
+}
+
+impl Timer {
+    pub(crate) fn new() -> Timer {
+        Timer {
+            state: Mutex::new(TimerState {
+                entries: BinaryHeap::new(),
+                next_seq: 0,
+                started: false,
+                closed: false,
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// 登记延迟执行一次的任务
+    pub(crate) fn schedule_once(shared_data: &Arc<ThreadPoolSharedData>, delay: Duration,
+                                job: Thunk<'static>) {
+        Timer::schedule(shared_data, Instant::now() + delay, TimerJob::Once(job));
+    }
+
+    /// 登记周期任务，首次执行在一个间隔之后
+    pub(crate) fn schedule_periodic<F>(shared_data: &Arc<ThreadPoolSharedData>, interval: Duration,
+                                       job: F) -> PeriodicHandle
+        where F: Fn() + Send + Sync + 'static
+    {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let periodic = Arc::new(PeriodicJob {
+            job: Box::new(job),
+            interval,
+            cancelled: cancelled.clone(),
+        });
+        Timer::schedule(shared_data, Instant::now() + interval, TimerJob::Periodic(periodic));
+        PeriodicHandle { cancelled }
+    }
+
+    fn schedule(shared_data: &Arc<ThreadPoolSharedData>, deadline: Instant, job: TimerJob) {
+        let timer = &shared_data.timer;
+        let mut state = timer.state.lock()
+            .expect("unable to lock timer");
+        if state.closed {
+            return;
+        }
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        state.entries.push(TimerEntry { deadline, seq, job });
+        if !state.started {  // 首次使用时才启动定时线程
+            state.started = true;
+            spawn_timer(shared_data);
+        }
+        timer.condvar.notify_one();
+    }
+
+    /// 线程池关闭时丢弃未到期的任务并让定时线程退出
+    pub(crate) fn close(&self) {
+        let entries = {
+            let mut state = self.state.lock()
+                .expect("unable to lock timer");
+            state.closed = true;
+            self.condvar.notify_one();
+            std::mem::take(&mut state.entries)
+        };
+        drop(entries);  // 在锁外丢弃任务，任务的析构可能再次访问线程池
+    }
+
+    /// 阻塞直到有任务到期，定时器关闭时返回None
+    fn next_expired(&self) -> Option<TimerEntry> {
+        let mut state = self.state.lock()
+            .expect("unable to lock timer");
+        loop {
+            if state.closed {
+                return None;
+            }
+            let now = Instant::now();
+            let timeout = match state.entries.peek() {
+                Some(entry) if entry.deadline <= now => return state.entries.pop(),
+                Some(entry) => Some(entry.deadline - now),
+                None => None,
+            };
+            state = match timeout {
+                Some(timeout) => self.condvar.wait_timeout(state, timeout)
+                    .expect("unable to wait for timer").0,
+                None => self.condvar.wait(state)
+                    .expect("unable to wait for timer"),
+            };
+        }
+    }
+}
+
+fn spawn_timer(shared_data: &Arc<ThreadPoolSharedData>) {
+    let mut builder = thread::Builder::new();
+    if let Some(ref name) = shared_data.name {
+        builder = builder.name(format!("{}-timer", name));
+    }
+    let timer_shared_data = shared_data.clone();
+    let handle = builder.spawn(move || {
+        while let Some(entry) = timer_shared_data.timer.next_expired() {
+            match entry.job {
+                TimerJob::Once(job) => timer_shared_data.push_job(job, Priority::Normal),
+                TimerJob::Periodic(periodic) => run_periodic(&timer_shared_data, periodic),
+            }
+        }
+    }).expect("unable to spawn timer thread");
+    shared_data.track_thread(handle);
+}
+
+/// 将周期任务提交到线程池，执行完毕后再登记下一次执行，因此同一周期任务不会并发执行
+fn run_periodic(shared_data: &Arc<ThreadPoolSharedData>, periodic: Arc<PeriodicJob>) {
+    if periodic.cancelled.load(Ordering::SeqCst) {
+        return;
+    }
+    let job_shared_data = shared_data.clone();
+    shared_data.push_job(Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| (periodic.job)()));
+        if !periodic.cancelled.load(Ordering::SeqCst) {  // 即使本次执行发生恐慌也继续后续的执行
+            let deadline = Instant::now() + periodic.interval;
+            Timer::schedule(&job_shared_data, deadline, TimerJob::Periodic(periodic));
+        }
+        if let Err(payload) = result {
+            panic::resume_unwind(payload);
+        }
+    }), Priority::Normal);
+}
@@ -0,0 +1,1037 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use thread_pool::{Builder, ExecuteError, PanicInfo, Priority, QueueFullPolicy, ThreadPool, TryExecuteError};
+
+const TEST_TASKS: usize = 4;
+
+#[test]
+fn join_waits_for_all_jobs() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let test_count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..42 {
+        let test_count = test_count.clone();
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(10));
+            test_count.fetch_add(1, Ordering::Relaxed);
+        }).unwrap();
+    }
+    pool.join();
+    assert_eq!(42, test_count.load(Ordering::Relaxed));
+}
+
+#[test]
+fn join_on_idle_pool_returns_immediately() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    pool.join();
+    pool.join();
+}
+
+#[test]
+fn join_can_be_called_repeatedly() {
+    let pool = Builder::new().num_threads(TEST_TASKS).build();
+    let test_count = Arc::new(AtomicUsize::new(0));
+    for round in 1..=3 {
+        for _ in 0..TEST_TASKS {
+            let test_count = test_count.clone();
+            pool.execute(move || {
+                test_count.fetch_add(1, Ordering::Relaxed);
+            }).unwrap();
+        }
+        pool.join();
+        assert_eq!(round * TEST_TASKS, test_count.load(Ordering::Relaxed));
+    }
+}
+
+#[test]
+fn join_from_multiple_threads() {
+    let pool = Arc::new(ThreadPool::new(TEST_TASKS));
+    let test_count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..TEST_TASKS {
+        let test_count = test_count.clone();
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(50));
+            test_count.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    let joiners: Vec<_> = (0..3)
+        .map(|_| {
+            let pool = pool.clone();
+            let test_count = test_count.clone();
+            thread::spawn(move || {
+                pool.join();
+                assert_eq!(TEST_TASKS, test_count.load(Ordering::SeqCst));
+            })
+        })
+        .collect();
+    for joiner in joiners {
+        joiner.join().unwrap();
+    }
+}
+
+#[test]
+fn sentinel_recovers_from_panics() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    for _ in 0..TEST_TASKS {
+        pool.execute(move || panic!("Ignore this panic, it must!")).unwrap();
+    }
+    pool.join();
+
+    // 所有工作线程发生恐慌后应被Sentinel重新拉起，线程池仍能并发执行TEST_TASKS个任务
+    let barrier = Arc::new(Barrier::new(TEST_TASKS + 1));
+    for _ in 0..TEST_TASKS {
+        let barrier = barrier.clone();
+        pool.execute(move || {
+            barrier.wait();
+        }).unwrap();
+    }
+    barrier.wait();
+    pool.join();
+}
+
+#[test]
+fn dropping_pool_still_runs_queued_jobs() {
+    let (tx, rx) = channel();
+    {
+        let pool = ThreadPool::new(1);
+        for i in 0..TEST_TASKS * 2 {
+            let tx = tx.clone();
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(5));
+                tx.send(i).unwrap();
+            }).unwrap();
+        }
+    }
+    drop(tx);
+    let mut received: Vec<usize> = rx.iter().collect();
+    received.sort_unstable();
+    assert_eq!((0..TEST_TASKS * 2).collect::<Vec<_>>(), received);
+}
+
+#[test]
+fn builder_configures_worker_threads() {
+    let pool = Builder::new().num_threads(2).build();
+    let barrier = Arc::new(Barrier::new(3));
+    for _ in 0..2 {
+        let barrier = barrier.clone();
+        pool.execute(move || {
+            barrier.wait();
+        }).unwrap();
+    }
+    barrier.wait();
+    pool.join();
+}
+
+#[test]
+#[should_panic]
+fn builder_rejects_zero_threads() {
+    Builder::new().num_threads(0);
+}
+
+/// 执行count个任务，返回同一时刻并发执行的最大任务数
+fn max_concurrency(pool: &ThreadPool, count: usize) -> usize {
+    let current = Arc::new(AtomicUsize::new(0));
+    let max = Arc::new(AtomicUsize::new(0));
+    for _ in 0..count {
+        let current = current.clone();
+        let max = max.clone();
+        pool.execute(move || {
+            let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            current.fetch_sub(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    pool.join();
+    max.load(Ordering::SeqCst)
+}
+
+#[test]
+fn set_num_threads_grows_pool() {
+    let pool = ThreadPool::new(1);
+    pool.set_num_threads(TEST_TASKS);
+    assert_eq!(TEST_TASKS, pool.max_count());
+
+    let barrier = Arc::new(Barrier::new(TEST_TASKS + 1));
+    for _ in 0..TEST_TASKS {
+        let barrier = barrier.clone();
+        pool.execute(move || {
+            barrier.wait();
+        }).unwrap();
+    }
+    barrier.wait();
+    pool.join();
+}
+
+#[test]
+fn set_num_threads_shrinks_pool() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    assert_eq!(TEST_TASKS, max_concurrency(&pool, TEST_TASKS * 2));
+
+    pool.set_num_threads(1);
+    assert_eq!(1, pool.max_count());
+    assert_eq!(1, max_concurrency(&pool, TEST_TASKS * 2));
+}
+
+#[test]
+fn set_num_threads_while_jobs_are_running() {
+    let pool = ThreadPool::new(2);
+    let test_count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..TEST_TASKS * 2 {
+        let test_count = test_count.clone();
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(10));
+            test_count.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    pool.set_num_threads(1);
+    pool.set_num_threads(TEST_TASKS);
+    pool.join();
+    assert_eq!(TEST_TASKS * 2, test_count.load(Ordering::SeqCst));
+    assert_eq!(TEST_TASKS, max_concurrency(&pool, TEST_TASKS * 2));
+}
+
+#[test]
+fn shrunk_pool_does_not_respawn_panicked_workers() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    pool.set_num_threads(2);
+    for _ in 0..TEST_TASKS {
+        pool.execute(move || panic!("Ignore this panic, it must!")).unwrap();
+    }
+    pool.join();
+    assert_eq!(2, max_concurrency(&pool, TEST_TASKS * 2));
+}
+
+#[test]
+fn counts_reflect_queued_and_active_jobs() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = channel::<()>();
+    let rx = Arc::new(Mutex::new(rx));
+    for _ in 0..3 {
+        let rx = rx.clone();
+        pool.execute(move || {
+            rx.lock().unwrap().recv().unwrap();
+        }).unwrap();
+    }
+    while pool.active_count() == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(1, pool.active_count());
+    assert_eq!(2, pool.queued_count());
+    assert_eq!(1, pool.max_count());
+
+    for _ in 0..3 {
+        tx.send(()).unwrap();
+    }
+    pool.join();
+    assert_eq!(0, pool.active_count());
+    assert_eq!(0, pool.queued_count());
+}
+
+#[test]
+fn stats_track_completed_jobs_and_panics() {
+    let pool = ThreadPool::new(2);
+    for i in 0..10 {
+        pool.execute(move || {
+            if i < 3 {
+                panic!("Ignore this panic, it must!");
+            }
+        }).unwrap();
+    }
+    pool.join();
+
+    let stats = pool.stats();
+    assert_eq!(3, stats.panic_count);
+    assert_eq!(3, pool.panic_count());
+    assert_eq!(7, stats.completed_count);
+    assert_eq!(2, stats.max_count);
+    assert_eq!(2, stats.thread_count);
+    assert_eq!(0, stats.queued_count);
+    assert_eq!(0, stats.active_count);
+    assert_eq!(vec![0, 1], stats.workers.iter().map(|w| w.index).collect::<Vec<_>>());
+    assert_eq!(7, stats.workers.iter().map(|w| w.jobs_completed).sum::<usize>());
+}
+
+#[test]
+fn stats_follow_resizing() {
+    let pool = ThreadPool::new(1);
+    pool.set_num_threads(3);
+    let stats = pool.stats();
+    assert_eq!(3, stats.max_count);
+    assert_eq!(3, stats.workers.len());
+
+    pool.set_num_threads(1);
+    for _ in 0..100 {
+        if pool.stats().workers.len() == 1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let stats = pool.stats();
+    assert_eq!(1, stats.thread_count);
+    assert_eq!(1, stats.workers.len());
+}
+
+/// 收集执行任务的工作线程名称
+fn worker_names(pool: &ThreadPool, count: usize) -> Vec<Option<String>> {
+    let barrier = Arc::new(Barrier::new(count));
+    let (tx, rx) = channel();
+    for _ in 0..count {
+        let barrier = barrier.clone();
+        let tx = tx.clone();
+        pool.execute(move || {
+            barrier.wait();  // 确保每个工作线程各执行一个任务
+            tx.send(thread::current().name().map(String::from)).unwrap();
+        }).unwrap();
+    }
+    drop(tx);
+    let mut names: Vec<_> = rx.iter().collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn builder_sets_thread_name() {
+    let pool = Builder::new()
+        .num_threads(2)
+        .thread_name("worker".to_owned())
+        .build();
+    assert_eq!(vec![Some("worker".to_owned()); 2], worker_names(&pool, 2));
+}
+
+#[test]
+fn builder_numbers_thread_names() {
+    let pool = Builder::new()
+        .num_threads(3)
+        .thread_name("worker".to_owned())
+        .numbered_thread_names(true)
+        .build();
+    let expected: Vec<_> = (0..3).map(|i| Some(format!("worker-{}", i))).collect();
+    assert_eq!(expected, worker_names(&pool, 3));
+
+    // 恐慌后重新拉起的线程沿用原有编号
+    for _ in 0..3 {
+        pool.execute(move || panic!("Ignore this panic, it must!")).unwrap();
+    }
+    pool.join();
+    assert_eq!(expected, worker_names(&pool, 3));
+}
+
+#[test]
+fn unnamed_pool_threads_have_no_name() {
+    let pool = Builder::new().num_threads(2).numbered_thread_names(true).build();
+    assert_eq!(vec![None; 2], worker_names(&pool, 2));
+}
+
+#[test]
+fn builder_sets_thread_stack_size() {
+    let pool = Builder::new()
+        .num_threads(1)
+        .thread_stack_size(16 * 1024 * 1024)
+        .build();
+    let (tx, rx) = channel();
+    pool.execute(move || {
+        let buf = [1u8; 8 * 1024 * 1024];  // 超过默认的2MB栈大小
+        tx.send(buf.iter().map(|&b| b as usize).sum::<usize>()).unwrap();
+    }).unwrap();
+    assert_eq!(8 * 1024 * 1024, rx.recv().unwrap());
+}
+
+#[test]
+fn scoped_jobs_borrow_from_stack() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let mut values = vec![0usize; 64];
+    let offset = 10;
+    pool.scoped(|scope| {
+        for (i, chunk) in values.chunks_mut(8).enumerate() {
+            let offset = &offset;
+            scope.execute(move || {
+                for (j, value) in chunk.iter_mut().enumerate() {
+                    *value = i * 8 + j + *offset;
+                }
+            }).unwrap();
+        }
+    });
+    assert_eq!((10..74).collect::<Vec<_>>(), values);
+}
+
+#[test]
+fn scoped_waits_for_all_jobs() {
+    let pool = ThreadPool::new(2);
+    let test_count = AtomicUsize::new(0);
+    let result = pool.scoped(|scope| {
+        for _ in 0..TEST_TASKS * 2 {
+            scope.execute(|| {
+                thread::sleep(Duration::from_millis(10));
+                test_count.fetch_add(1, Ordering::SeqCst);
+            }).unwrap();
+        }
+        "done"
+    });
+    assert_eq!("done", result);
+    assert_eq!(TEST_TASKS * 2, test_count.load(Ordering::SeqCst));
+}
+
+#[test]
+fn scoped_reraises_job_panics() {
+    let pool = ThreadPool::new(2);
+    let test_count = AtomicUsize::new(0);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        pool.scoped(|scope| {
+            scope.execute(|| panic!("scoped job failed")).unwrap();
+            for _ in 0..TEST_TASKS {
+                scope.execute(|| {
+                    thread::sleep(Duration::from_millis(10));
+                    test_count.fetch_add(1, Ordering::SeqCst);
+                }).unwrap();
+            }
+        });
+    }));
+    let payload = result.unwrap_err();
+    assert_eq!(Some(&"scoped job failed"), payload.downcast_ref::<&str>());
+    // 恐慌重新抛出前其余任务已全部结束
+    assert_eq!(TEST_TASKS, test_count.load(Ordering::SeqCst));
+
+    // 作用域任务的恐慌不会影响线程池后续使用
+    assert_eq!(1, pool.panic_count());
+    assert_eq!(2, max_concurrency(&pool, 4));
+}
+
+#[test]
+fn scoped_waits_for_discarded_jobs_to_drop() {
+    /// 析构时访问借用的数据
+    struct Probe<'a>(&'a AtomicUsize);
+
+    impl Drop for Probe<'_> {
+        fn drop(&mut self) {
+            thread::sleep(Duration::from_millis(20));
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let pool = ThreadPool::new(1);
+    let dropped = AtomicUsize::new(0);
+    let (tx, rx) = channel::<()>();
+    thread::scope(|s| {
+        let discarded = pool.scoped(|scope| {
+            scope.execute(move || {
+                let _ = rx.recv();
+            }).unwrap();
+            while pool.active_count() == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            for _ in 0..4 {
+                let probe = Probe(&dropped);
+                scope.execute(move || drop(probe)).unwrap();
+            }
+            s.spawn(|| {
+                // 最后一个被丢弃的任务析构期间放行正在执行的任务
+                while dropped.load(Ordering::SeqCst) < 3 {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                tx.send(()).unwrap();
+            });
+            s.spawn(|| pool.shutdown_now())
+        });
+        // 被丢弃任务的析构全部结束后scoped才返回
+        assert_eq!(4, dropped.load(Ordering::SeqCst));
+        assert!(discarded.join().unwrap() > 0);
+    });
+}
+
+#[test]
+fn spawn_returns_job_result() {
+    let pool = ThreadPool::new(2);
+    let handles: Vec<_> = (0..8).map(|i| pool.spawn(move || i * i)).collect();
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(vec![0, 1, 4, 9, 16, 25, 36, 49], results);
+}
+
+#[test]
+fn spawn_reports_panic_payload() {
+    let pool = ThreadPool::new(1);
+    let handle = pool.spawn(|| -> usize { panic!("spawned job failed") });
+    let payload = handle.join().unwrap_err().into_panic();
+    assert_eq!(Some(&"spawned job failed"), payload.downcast_ref::<&str>());
+    assert_eq!(1, pool.panic_count());
+    assert_eq!(42, pool.spawn(|| 42).join().unwrap());
+}
+
+#[test]
+fn try_join_does_not_block() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = channel::<()>();
+    let mut handle = pool.spawn(move || {
+        rx.recv().unwrap();
+        "finished"
+    });
+    assert!(handle.try_join().is_none());
+    assert!(!handle.is_finished());
+    tx.send(()).unwrap();
+    pool.join();
+    assert!(handle.is_finished());
+    assert_eq!("finished", handle.try_join().unwrap().unwrap());
+    assert!(handle.try_join().is_none());  // 结果只能取走一次
+    assert!(handle.is_finished());
+}
+
+#[test]
+fn task_handle_is_a_future() {
+    let pool = ThreadPool::new(2);
+    let first = pool.spawn(|| {
+        thread::sleep(Duration::from_millis(20));
+        20
+    });
+    let second = pool.spawn(|| 22);
+    let sum = futures::executor::block_on(async move {
+        first.await.unwrap() + second.await.unwrap()
+    });
+    assert_eq!(42, sum);
+}
+
+/// 创建单线程、容量为1的线程池，返回时工作线程被阻塞且队列已满，向返回的Sender发送两次即可放行
+fn saturated_pool(policy: QueueFullPolicy) -> (ThreadPool, std::sync::mpsc::Sender<()>) {
+    let pool = Builder::new()
+        .num_threads(1)
+        .queue_capacity(1)
+        .queue_full_policy(policy)
+        .build();
+    let (tx, rx) = channel::<()>();
+    let rx = Arc::new(Mutex::new(rx));
+    for _ in 0..2 {
+        let rx = rx.clone();
+        pool.execute(move || {
+            rx.lock().unwrap().recv().unwrap();
+        }).unwrap();
+        while pool.active_count() == 0 {  // 等待工作线程取走第一个任务
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+    assert_eq!(1, pool.queued_count());
+    (pool, tx)
+}
+
+#[test]
+fn try_execute_returns_job_when_queue_is_full() {
+    let (pool, tx) = saturated_pool(QueueFullPolicy::Block);
+    let (result_tx, result_rx) = channel();
+    let job = move || result_tx.send("ran").unwrap();
+    let job = match pool.try_execute(job) {
+        Err(TryExecuteError::Full(job)) => job,
+        other => panic!("queue should be full, got {:?}", other),
+    };
+    tx.send(()).unwrap();
+    tx.send(()).unwrap();
+    pool.join();
+    pool.try_execute(job).unwrap();
+    assert_eq!("ran", result_rx.recv().unwrap());
+}
+
+#[test]
+fn execute_blocks_when_queue_is_full() {
+    let (pool, tx) = saturated_pool(QueueFullPolicy::Block);
+    let pool = Arc::new(pool);
+    let submitted = Arc::new(AtomicUsize::new(0));
+    let producer = {
+        let pool = pool.clone();
+        let submitted = submitted.clone();
+        thread::spawn(move || {
+            pool.execute(|| {}).unwrap();
+            submitted.store(1, Ordering::SeqCst);
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(0, submitted.load(Ordering::SeqCst));
+
+    tx.send(()).unwrap();
+    producer.join().unwrap();
+    assert_eq!(1, submitted.load(Ordering::SeqCst));
+    tx.send(()).unwrap();
+    pool.join();
+}
+
+#[test]
+fn reject_policy_returns_error_when_queue_is_full() {
+    let (pool, tx) = saturated_pool(QueueFullPolicy::Reject);
+    assert_eq!(Err(ExecuteError::QueueFull), pool.execute(|| {}));
+    assert!(pool.spawn(|| 1).join().unwrap_err().is_cancelled());
+    tx.send(()).unwrap();
+    tx.send(()).unwrap();
+    pool.join();
+    assert_eq!(Ok(()), pool.execute(|| {}));
+}
+
+#[test]
+fn caller_runs_policy_runs_job_on_caller_thread() {
+    let (pool, tx) = saturated_pool(QueueFullPolicy::CallerRuns);
+    let (id_tx, id_rx) = channel();
+    pool.execute(move || id_tx.send(thread::current().id()).unwrap()).unwrap();
+    assert_eq!(thread::current().id(), id_rx.recv().unwrap());
+    tx.send(()).unwrap();
+    tx.send(()).unwrap();
+    pool.join();
+}
+
+/// 在工作线程中递归提交任务，共提交2^depth - 1个任务
+fn fan_out(pool: &Arc<ThreadPool>, counter: &Arc<AtomicUsize>, depth: usize) {
+    counter.fetch_add(1, Ordering::SeqCst);
+    if depth > 1 {
+        for _ in 0..2 {
+            let inner_pool = pool.clone();
+            let counter = counter.clone();
+            pool.execute(move || fan_out(&inner_pool, &counter, depth - 1)).unwrap();
+        }
+    }
+}
+
+#[test]
+fn jobs_spawned_from_workers_are_run() {
+    let pool = Arc::new(ThreadPool::new(TEST_TASKS));
+    let counter = Arc::new(AtomicUsize::new(0));
+    {
+        let inner_pool = pool.clone();
+        let counter = counter.clone();
+        pool.execute(move || fan_out(&inner_pool, &counter, 10)).unwrap();
+    }
+    pool.join();
+    assert_eq!((1 << 10) - 1, counter.load(Ordering::SeqCst));
+}
+
+#[test]
+fn jobs_spawned_from_workers_ignore_queue_capacity() {
+    let pool = Arc::new(Builder::new().num_threads(1).queue_capacity(1).build());
+    let counter = Arc::new(AtomicUsize::new(0));
+    {
+        let inner_pool = pool.clone();
+        let counter = counter.clone();
+        pool.execute(move || fan_out(&inner_pool, &counter, 6)).unwrap();
+    }
+    pool.join();
+    assert_eq!((1 << 6) - 1, counter.load(Ordering::SeqCst));
+}
+
+#[test]
+fn local_jobs_survive_worker_panic() {
+    let pool = Arc::new(ThreadPool::new(1));
+    let counter = Arc::new(AtomicUsize::new(0));
+    {
+        let inner_pool = pool.clone();
+        let counter = counter.clone();
+        pool.execute(move || {
+            for _ in 0..TEST_TASKS {
+                let counter = counter.clone();
+                inner_pool.execute(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }).unwrap();
+            }
+            panic!("Ignore this panic, it must!");
+        }).unwrap();
+    }
+    pool.join();
+    assert_eq!(TEST_TASKS, counter.load(Ordering::SeqCst));
+    assert_eq!(1, pool.panic_count());
+}
+
+#[test]
+fn idle_workers_steal_local_jobs() {
+    let pool = Arc::new(ThreadPool::new(TEST_TASKS));
+    let barrier = Arc::new(Barrier::new(TEST_TASKS));
+    {
+        let inner_pool = pool.clone();
+        pool.execute(move || {
+            // 所有任务都进入当前线程的本地队列，只有被其他线程窃取才能同时等待屏障
+            for _ in 0..TEST_TASKS {
+                let barrier = barrier.clone();
+                inner_pool.execute(move || {
+                    barrier.wait();
+                }).unwrap();
+            }
+        }).unwrap();
+    }
+    pool.join();
+}
+
+#[test]
+fn shutdown_runs_queued_jobs_and_joins_workers() {
+    let pool = ThreadPool::new(2);
+    let test_count = Arc::new(AtomicUsize::new(0));
+    for _ in 0..TEST_TASKS * 2 {
+        let test_count = test_count.clone();
+        pool.execute(move || {
+            thread::sleep(Duration::from_millis(10));
+            test_count.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    pool.shutdown();
+    assert!(pool.is_shutdown());
+    assert_eq!(TEST_TASKS * 2, test_count.load(Ordering::SeqCst));
+    assert_eq!(0, pool.stats().thread_count);
+    assert!(pool.stats().workers.is_empty());
+
+    assert_eq!(Err(ExecuteError::Shutdown), pool.execute(|| {}));
+    match pool.try_execute(|| {}) {
+        Err(TryExecuteError::Shutdown(_)) => {}
+        other => panic!("pool should be shut down, got {:?}", other),
+    }
+    assert!(pool.spawn(|| 1).join().unwrap_err().is_cancelled());
+    pool.join();
+}
+
+#[test]
+fn shutdown_now_discards_queued_jobs() {
+    let (pool, tx) = saturated_pool(QueueFullPolicy::Block);
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(()).unwrap();  // 只放行正在执行的任务
+        tx
+    });
+    assert_eq!(1, pool.shutdown_now());
+    let _tx = releaser.join().unwrap();
+    assert_eq!(0, pool.queued_count());
+    assert_eq!(0, pool.stats().thread_count);
+    pool.join();
+}
+
+#[test]
+fn shutdown_now_cancels_spawned_jobs() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = channel::<()>();
+    pool.execute(move || {
+        rx.recv().unwrap();
+    }).unwrap();
+    while pool.active_count() == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    let handles: Vec<_> = (0..TEST_TASKS).map(|i| pool.spawn(move || i)).collect();
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(()).unwrap();
+    });
+    assert_eq!(TEST_TASKS, pool.shutdown_now());
+    releaser.join().unwrap();
+    for handle in handles {
+        assert!(handle.join().unwrap_err().is_cancelled());
+    }
+}
+
+#[test]
+fn shutdown_wakes_blocked_producers() {
+    let (pool, tx) = saturated_pool(QueueFullPolicy::Block);
+    let pool = Arc::new(pool);
+    let producer = {
+        let pool = pool.clone();
+        thread::spawn(move || pool.execute(|| {}))
+    };
+    thread::sleep(Duration::from_millis(50));
+    let releaser = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(()).unwrap();
+        tx.send(()).unwrap();
+    });
+    pool.shutdown();
+    releaser.join().unwrap();
+    assert_eq!(Err(ExecuteError::Shutdown), producer.join().unwrap());
+}
+
+#[test]
+fn shutdown_from_worker_does_not_deadlock() {
+    let pool = Arc::new(ThreadPool::new(2));
+    let (tx, rx) = channel();
+    {
+        let inner_pool = pool.clone();
+        pool.execute(move || {
+            inner_pool.shutdown();
+            tx.send(inner_pool.is_shutdown()).unwrap();
+        }).unwrap();
+    }
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+    pool.shutdown();
+}
+
+#[test]
+fn higher_priority_jobs_run_first() {
+    let pool = ThreadPool::new(1);
+    let (tx, rx) = channel::<()>();
+    pool.execute(move || rx.recv().unwrap()).unwrap();
+    while pool.active_count() == 0 {
+        thread::yield_now();
+    }
+    let order = Arc::new(Mutex::new(Vec::new()));
+    for &priority in &[Priority::Low, Priority::Normal, Priority::High] {
+        let order = order.clone();
+        pool.execute_with_priority(priority, move || order.lock().unwrap().push(priority)).unwrap();
+    }
+    tx.send(()).unwrap();
+    pool.join();
+    assert_eq!(vec![Priority::High, Priority::Normal, Priority::Low], *order.lock().unwrap());
+}
+
+#[test]
+fn execute_after_waits_for_delay() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let (tx, rx) = channel();
+    let start = std::time::Instant::now();
+    pool.execute_after(Duration::from_millis(50), move || tx.send(start.elapsed()).unwrap()).unwrap();
+    let elapsed = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(elapsed >= Duration::from_millis(50));
+}
+
+#[test]
+fn execute_every_repeats_until_cancelled() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let runs = Arc::new(AtomicUsize::new(0));
+    let handle = {
+        let runs = runs.clone();
+        pool.execute_every(Duration::from_millis(5), move || {
+            runs.fetch_add(1, Ordering::SeqCst);
+        }).unwrap()
+    };
+    while runs.load(Ordering::SeqCst) < 3 {
+        thread::sleep(Duration::from_millis(5));
+    }
+    handle.cancel();
+    assert!(handle.is_cancelled());
+    thread::sleep(Duration::from_millis(20));  // 等待取消前已经提交的那一次执行结束
+    let stopped_at = runs.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(stopped_at, runs.load(Ordering::SeqCst));
+}
+
+#[test]
+fn pending_timers_are_discarded_on_shutdown() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let ran = Arc::new(AtomicUsize::new(0));
+    {
+        let ran = ran.clone();
+        pool.execute_after(Duration::from_millis(50), move || {
+            ran.fetch_add(1, Ordering::SeqCst);
+        }).unwrap();
+    }
+    pool.shutdown();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(0, ran.load(Ordering::SeqCst));
+    assert_eq!(Err(ExecuteError::Shutdown), pool.execute_after(Duration::from_millis(1), || {}));
+    assert!(pool.execute_every(Duration::from_millis(1), || {}).is_err());
+}
+
+#[test]
+fn panic_handler_reports_label_and_message() {
+    let (tx, rx) = channel();
+    let tx = Mutex::new(tx);
+    let pool = Builder::new()
+        .num_threads(1)
+        .thread_name("reporter".to_owned())
+        .panic_handler(move |info: PanicInfo| tx.lock().unwrap().send(info).unwrap())
+        .build();
+    pool.execute_with_label("import", || panic!("bad row {}", 7)).unwrap();
+    let info = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(Some("import"), info.label.as_deref());
+    assert_eq!(Some("bad row 7"), info.message.as_deref());
+    assert_eq!(Some("reporter"), info.thread_name.as_deref());
+    assert_eq!("job 'import' panicked in thread 'reporter': bad row 7", info.to_string());
+
+    pool.execute(|| panic!("unlabeled")).unwrap();
+    let info = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(None, info.label);
+    assert_eq!(Some("unlabeled"), info.message.as_deref());
+}
+
+#[test]
+fn join_and_check_reports_panics_since_last_join() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    assert!(pool.join_and_check().is_ok());
+    pool.execute_with_label("first", || panic!("boom")).unwrap();
+    pool.execute(|| {}).unwrap();
+    let panics = pool.join_and_check().unwrap_err();
+    assert_eq!(1, panics.len());
+    assert_eq!(Some("first"), panics[0].label.as_deref());
+    assert!(pool.join_and_check().is_ok());
+
+    pool.execute(|| panic!("cleared")).unwrap();
+    pool.join();
+    assert!(pool.join_and_check().is_ok());
+    assert_eq!(2, pool.panic_count());
+}
+
+#[test]
+fn thread_hooks_run_once_per_worker() {
+    thread_local! {
+        static SCRATCH: std::cell::RefCell<Option<Vec<u8>>> = const { std::cell::RefCell::new(None) };
+    }
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let pool = {
+        let started = started.clone();
+        let stopped = stopped.clone();
+        Builder::new()
+            .num_threads(TEST_TASKS)
+            .on_thread_start(move |index| {
+                SCRATCH.with(|scratch| *scratch.borrow_mut() = Some(Vec::with_capacity(64)));
+                started.lock().unwrap().push(index);
+            })
+            .on_thread_stop(move |index| stopped.lock().unwrap().push(index))
+            .build()
+    };
+    let initialized = Arc::new(AtomicUsize::new(0));
+    for _ in 0..100 {
+        let initialized = initialized.clone();
+        pool.execute(move || {
+            if SCRATCH.with(|scratch| scratch.borrow().is_some()) {
+                initialized.fetch_add(1, Ordering::SeqCst);
+            }
+        }).unwrap();
+    }
+    pool.join();
+    assert_eq!(100, initialized.load(Ordering::SeqCst));
+    pool.shutdown();
+    let mut started = started.lock().unwrap().clone();
+    let mut stopped = stopped.lock().unwrap().clone();
+    started.sort_unstable();
+    stopped.sort_unstable();
+    assert_eq!((0..TEST_TASKS).collect::<Vec<_>>(), started);
+    assert_eq!(started, stopped);
+}
+
+#[test]
+fn thread_hooks_run_again_for_respawned_workers() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let started = started.clone();
+        let stopped = stopped.clone();
+        Builder::new()
+            .num_threads(1)
+            .on_thread_start(move |_| {
+                started.fetch_add(1, Ordering::SeqCst);
+            })
+            .on_thread_stop(move |_| {
+                stopped.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+    };
+    pool.execute(|| panic!("respawn")).unwrap();
+    pool.join();
+    pool.shutdown();
+    assert_eq!(2, started.load(Ordering::SeqCst));
+    assert_eq!(2, stopped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn panicking_start_hook_does_not_respawn() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let started = started.clone();
+        Builder::new()
+            .num_threads(2)
+            .on_thread_start(move |_| {
+                started.fetch_add(1, Ordering::SeqCst);
+                panic!("unable to connect");
+            })
+            .build()
+    };
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(2, started.load(Ordering::SeqCst));  // 回调恐慌的线程不会被重新拉起
+    assert_eq!(0, pool.active_count());
+    assert_eq!(2, pool.panic_count());
+    let panics = pool.join_and_check().unwrap_err();  // 不会因active_count错误而永远阻塞
+    assert_eq!(2, panics.len());
+    assert_eq!(Some("on_thread_start"), panics[0].label.as_deref());
+    assert_eq!(Some("unable to connect"), panics[0].message.as_deref());
+    pool.join();
+}
+
+#[test]
+fn panicking_stop_hook_is_reported() {
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let stopped = stopped.clone();
+        Builder::new()
+            .num_threads(2)
+            .on_thread_stop(move |_| {
+                stopped.fetch_add(1, Ordering::SeqCst);
+                panic!("stop failed");
+            })
+            .build()
+    };
+    let labels = |panics: Vec<PanicInfo>| {
+        let mut labels: Vec<_> = panics.into_iter().map(|info| info.label).collect();
+        labels.sort();
+        labels
+    };
+    pool.execute(|| panic!("job failed")).unwrap();  // 恐慌的线程退出时同样调用退出回调
+    let panics = pool.join_and_check().unwrap_err();
+    assert_eq!(vec![None, Some("on_thread_stop".to_owned())], labels(panics));
+    assert_eq!(0, pool.active_count());
+    assert_eq!(1, stopped.load(Ordering::SeqCst));
+    pool.shutdown();
+    assert_eq!(3, stopped.load(Ordering::SeqCst));  // 正常退出时回调恐慌也不会重新拉起线程
+    assert_eq!(0, pool.active_count());
+    let panics = pool.join_and_check().unwrap_err();
+    assert_eq!(vec![Some("on_thread_stop".to_owned()); 2], labels(panics));
+}
+
+#[test]
+fn par_map_preserves_order() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let expected: Vec<u64> = (0..1000u64).map(|i| i * i).collect();
+    assert_eq!(expected, pool.par_map(0..1000u64, |i| i * i));
+    assert_eq!(vec![2], pool.par_map(vec![1], |i| i * 2));
+    assert!(pool.par_map(Vec::<u64>::new(), |i| i * 2).is_empty());
+}
+
+#[test]
+fn par_map_can_borrow_local_data() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let words = vec!["alpha".to_owned(), "beta".to_owned(), "gamma".to_owned()];
+    let lengths = pool.par_map(&words, |word| word.len());
+    assert_eq!(vec![5, 4, 5], lengths);
+}
+
+#[test]
+fn par_for_each_visits_every_item() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    let sum = AtomicUsize::new(0);
+    pool.par_for_each(1..=100usize, |i| {
+        sum.fetch_add(i, Ordering::SeqCst);
+    });
+    assert_eq!(5050, sum.load(Ordering::SeqCst));
+}
+
+#[test]
+fn par_reduce_matches_sequential_fold() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    assert_eq!((1..=1000u64).sum::<u64>(), pool.par_reduce(1..=1000u64, || 0, |a, b| a + b));
+    // 字符串拼接不满足交换律，用来检查各块的合并顺序
+    let letters: Vec<String> = (b'a'..=b'z').map(|c| (c as char).to_string()).collect();
+    let joined = pool.par_reduce(letters.clone(), String::new, |a, b| a + &b);
+    assert_eq!(letters.concat(), joined);
+    assert_eq!(0, pool.par_reduce(Vec::new(), || 0, |a, b| a + b));
+}
+
+#[test]
+fn par_map_runs_rejected_chunks_on_caller() {
+    let pool = Builder::new()
+        .num_threads(1)
+        .queue_capacity(1)
+        .queue_full_policy(QueueFullPolicy::Reject)
+        .build();
+    let expected: Vec<usize> = (0..100).map(|i| i + 1).collect();
+    assert_eq!(expected, pool.par_map(0..100, |i| i + 1));
+    pool.shutdown();
+    assert_eq!(expected, pool.par_map(0..100, |i| i + 1));
+}
+
+#[test]
+#[should_panic(expected = "par_map job panicked")]
+fn par_map_propagates_panics() {
+    let pool = ThreadPool::new(TEST_TASKS);
+    pool.par_map(0..100, |i| {
+        if i == 42 {
+            panic!("par_map job panicked");
+        }
+        i
+    });
+}